@@ -10,7 +10,7 @@ const BASH_COMPLETION: &str = r#"_muxed() {
     cur="${COMP_WORDS[COMP_CWORD]}"
     prev="${COMP_WORDS[COMP_CWORD-1]}"
 
-    opts="list ls edit load new snapshot autocomplete"
+    opts="list ls diff edit export load new restart schema snapshot stop validate workspace autocomplete"
     projects="$(muxed list -1 2>/dev/null)"
 
     # First argument after 'muxed' — offer commands + project names
@@ -21,10 +21,14 @@ const BASH_COMPLETION: &str = r#"_muxed() {
 
     # If the previous word is a command that expects a project name
     case "${prev}" in
-        edit|load|snapshot)
+        diff|edit|export|load|restart|snapshot|stop|validate)
             COMPREPLY=( $(compgen -W "${projects}" -- "${cur}") )
             return 0
             ;;
+        workspace)
+            COMPREPLY=( $(compgen -W "up down" -- "${cur}") )
+            return 0
+            ;;
     esac
 }
 complete -F _muxed muxed
@@ -14,11 +14,14 @@ end
 function __fish_muxed_needs_project
     set cmd (commandline -opc)
     set sub (string split ' ' -- $cmd)[2]
-    contains -- $sub edit load snapshot
+    contains -- $sub diff edit export load restart snapshot stop validate
 end
 
 # Subcommands
-complete -c muxed -n '__fish_muxed_needs_command' -a "list ls edit load new snapshot autocomplete"
+complete -c muxed -n '__fish_muxed_needs_command' -a "list ls diff edit export load new restart schema snapshot stop validate workspace autocomplete"
+
+# Workspace actions
+complete -c muxed -n '__fish_seen_subcommand_from workspace' -a "up down"
 
 # Project completions for commands that expect a project
 complete -c muxed -n '__fish_muxed_needs_project' -a '(__fish_muxed_projects)'
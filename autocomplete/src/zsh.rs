@@ -8,7 +8,7 @@ _muxed() {
     local projectdir=~/.muxed
     local -a commands
     local -a projects
-    commands=(list ls edit load new snapshot autocomplete)
+    commands=(list ls diff edit export load new restart schema snapshot stop validate workspace autocomplete)
 
     # Guaranteed stripping via for-loop just to be safe with all shells
     projects=("${(@f)$(muxed list -1 2>/dev/null)}")
@@ -18,9 +18,12 @@ _muxed() {
         compadd -- $commands $projects
     elif (( CURRENT == 3 )); then
         # When completing after a command that expects a project name
-        if [[ "$words[2]" == (edit|load|snapshot) ]]; then
+        if [[ "$words[2]" == (diff|edit|export|load|restart|snapshot|stop|validate) ]]; then
             compadd -- $projects
             return
+        elif [[ "$words[2]" == workspace ]]; then
+            compadd -- up down
+            return
         fi
     fi
 }
@@ -4,6 +4,7 @@ use serde::Deserialize;
 
 /// The args struct for taking arguments passed in from the command line
 /// and making it easier to pass around.
+/// `flag_backend` how load sends commands to tmux, `batch`, `control` or `each`
 /// `flag_d` is whether the session should be daemonized
 /// `flag_v` to print the version
 /// `flag_f` to force overwrite of a file
@@ -11,31 +12,92 @@ use serde::Deserialize;
 /// `flag_t` the session to read from
 /// `flag_1` whether to use new-lines for list command
 /// `flag_debug` run inline print statements for debugging
-/// `arg_project` the project file to read
+/// `flag_format` the format to export a project to
+/// `flag_dry_run` print the planned tmux commands instead of running them
+/// `flag_reconcile` create what's missing from an already running session
+/// `flag_into` the running session to add a project's windows to
+/// `flag_into_current` add a project's windows to the session muxed runs in
+/// `flag_all` validate every project in the project directory, or snapshot
+/// every running session
+/// `flag_allow` the only programs whose commands a snapshot records
+/// `flag_deny` programs whose commands a snapshot never records
+/// `flag_json` print the diff as JSON
+/// `flag_keep_going` build the rest of a project when a tmux command fails
+/// `flag_with_scrollback` save each pane's history with a snapshot
+/// `arg_project` the project file to read, or a first `key=value` var when the
+/// local project is loaded without naming it
+/// `arg_vars` `key=value` pairs used to fill in a project's template variables
+/// `arg_workspace` the workspace file to read
+/// `cmd_diff` if `true` run diff command
 /// `cmd_edit` if `true` run edit command
+/// `cmd_export` if `true` run export command
 /// `cmd_load` if `true` run load command (This is also the default command)
 /// `cmd_new` if `true` run new command
+/// `cmd_restart` if `true` run restart command
+/// `cmd_schema` if `true` run schema command
 /// `cmd_snapshot` if `true` run snapshot command
+/// `cmd_stop` if `true` run stop command
 /// `cmd_list` if `true` run list command
+/// `cmd_validate` if `true` run validate command
+/// `cmd_workspace` if `true` run workspace command, with `cmd_up` or `cmd_down`
 ///
 #[derive(Debug, Deserialize)]
 pub struct Args {
+    pub flag_all: bool,
+    pub flag_allow: Option<String>,
+    pub flag_backend: Option<String>,
     pub flag_debug: bool,
+    pub flag_deny: Option<String>,
+    pub flag_dry_run: bool,
     pub flag_d: bool,
     pub flag_f: bool,
+    pub flag_format: Option<String>,
+    pub flag_into: Option<String>,
+    pub flag_into_current: bool,
+    pub flag_json: bool,
+    pub flag_keep_going: bool,
     pub flag_p: Option<String>,
+    pub flag_reconcile: bool,
     pub flag_t: Option<String>,
     pub flag_template: Option<String>,
     pub flag_v: bool,
+    pub flag_with_scrollback: bool,
     pub flag_1: bool,
     pub arg_project: String,
+    pub arg_vars: Vec<String>,
+    pub arg_workspace: String,
+    pub cmd_diff: bool,
     pub cmd_edit: bool,
+    pub cmd_export: bool,
     pub cmd_load: bool,
     pub cmd_new: bool,
+    pub cmd_restart: bool,
+    pub cmd_schema: bool,
     pub cmd_snapshot: bool,
+    pub cmd_stop: bool,
     pub cmd_list: bool,
     pub cmd_ls: bool,
     pub cmd_autocomplete: bool,
+    pub cmd_validate: bool,
+    pub cmd_workspace: bool,
+    pub cmd_up: bool,
+    pub cmd_down: bool,
+}
+
+impl Args {
+    /// Whether the project argument is a `key=value` var, given in place of a
+    /// project name to load the local project with.
+    pub fn project_is_var(&self) -> bool {
+        self.arg_project.contains('=')
+    }
+
+    /// Every `key=value` var given, including one in place of the project name.
+    pub fn vars(&self) -> Vec<String> {
+        match self.project_is_var() {
+            true => [std::slice::from_ref(&self.arg_project), &self.arg_vars].concat(),
+            false => self.arg_vars.clone(),
+        }
+    }
 }
 
 impl Default for Args {
@@ -44,20 +106,43 @@ impl Default for Args {
 
         Args {
             arg_project: name,
+            arg_vars: vec![],
+            arg_workspace: String::new(),
+            cmd_diff: false,
             cmd_edit: false,
+            cmd_export: false,
             cmd_load: false,
             cmd_new: true,
+            cmd_restart: false,
+            cmd_schema: false,
             cmd_snapshot: false,
+            cmd_stop: false,
             cmd_list: false,
             cmd_ls: false,
             cmd_autocomplete: false,
+            cmd_validate: false,
+            cmd_workspace: false,
+            cmd_up: false,
+            cmd_down: false,
+            flag_all: false,
+            flag_allow: None,
+            flag_backend: None,
             flag_d: true,
             flag_debug: false,
+            flag_deny: None,
+            flag_dry_run: false,
             flag_f: false,
+            flag_format: None,
+            flag_into: None,
+            flag_into_current: false,
+            flag_json: false,
+            flag_keep_going: false,
             flag_p: None,
+            flag_reconcile: false,
             flag_t: None,
             flag_template: None,
             flag_v: false,
+            flag_with_scrollback: false,
             flag_1: false,
         }
     }
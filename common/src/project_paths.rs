@@ -2,16 +2,23 @@ use crate::args::Args;
 use crate::error::CommonError;
 #[cfg(not(any(test, doctest)))]
 use dirs::home_dir;
-use std::path::PathBuf;
+use std::env;
+use std::path::{Component, Path, PathBuf};
 
 pub const CONFIG_EXTENSION: &str = "yml";
 static MUXED_FOLDER: &str = ".muxed";
+static TEMPLATE_FILE: &str = ".template";
+
+/// The project files looked for, in order, when discovering a project from
+/// the current directory.
+pub static LOCAL_PROJECT_FILES: [&str; 2] = [".muxed.yml", "muxed.yml"];
 
 pub struct ProjectPaths {
     pub home_directory: PathBuf,
     pub project_directory: PathBuf,
     pub project_file: PathBuf,
     pub template_file: PathBuf,
+    pub local: bool,
 }
 
 impl ProjectPaths {
@@ -26,9 +33,68 @@ impl ProjectPaths {
             project_directory,
             project_file,
             template_file,
+            local: false,
+        }
+    }
+
+    /// The paths for a project file living alongside the code it's for, like
+    /// a `.muxed.yml` committed to a repository. The directory holding the file
+    /// stands in as the project directory.
+    pub fn local(home_directory: PathBuf, project_file: PathBuf) -> ProjectPaths {
+        let project_directory = project_file
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let template_file = project_directory
+            .join(TEMPLATE_FILE)
+            .with_extension(CONFIG_EXTENSION);
+
+        ProjectPaths {
+            home_directory,
+            project_directory,
+            project_file,
+            template_file,
+            local: true,
         }
     }
 
+    /// Resolve the paths for the project named in `args`. When no project is
+    /// named, or a `key=value` var is given in its place, walk up from the
+    /// current directory to find a local project file instead.
+    pub fn resolve(args: &Args) -> Result<ProjectPaths, CommonError> {
+        let cwd = env::current_dir().map_err(|e| CommonError::ProjectPaths(e.to_string()))?;
+        ProjectPaths::resolve_in(args, &cwd)
+    }
+
+    fn resolve_in(args: &Args, cwd: &Path) -> Result<ProjectPaths, CommonError> {
+        if !args.arg_project.is_empty() && !args.project_is_var() {
+            return ProjectPaths::try_from(args);
+        }
+
+        let project_file = find_local_project(cwd).ok_or_else(|| {
+            CommonError::ProjectPaths(format!(
+                "No project was given and no {} was found in `{}` or any of its parents",
+                LOCAL_PROJECT_FILES.join(" or "),
+                cwd.display()
+            ))
+        })?;
+        let homedir =
+            homedir().ok_or(CommonError::ProjectPaths("homedir not found".to_string()))?;
+
+        Ok(ProjectPaths::local(homedir, project_file))
+    }
+
+    /// The name of a local project, taken from the directory it lives in.
+    /// Projects in the muxed directory are named by the user instead, so this
+    /// is `None` for them.
+    pub fn local_name(&self) -> Option<&str> {
+        if !self.local {
+            return None;
+        }
+
+        self.project_directory.file_name().and_then(|n| n.to_str())
+    }
+
     pub fn from_strs(
         home_directory: &str,
         project_directory: &str,
@@ -49,6 +115,7 @@ impl ProjectPaths {
             project_directory,
             project_file,
             template_file,
+            local: false,
         }
     }
 }
@@ -98,7 +165,7 @@ impl TryFrom<&Args> for ProjectPaths {
         let project_filename = PathBuf::from(&args.arg_project).with_extension(CONFIG_EXTENSION);
         let project_fullpath = project_directory.join(project_filename);
 
-        let template_filename: &str = args.flag_template.as_deref().unwrap_or(".template");
+        let template_filename: &str = args.flag_template.as_deref().unwrap_or(TEMPLATE_FILE);
         let template_filename = PathBuf::from(template_filename).with_extension(CONFIG_EXTENSION);
         let template_fullpath = project_directory.join(template_filename);
 
@@ -111,6 +178,15 @@ impl TryFrom<&Args> for ProjectPaths {
     }
 }
 
+/// Walk up from `start` looking for a local project file. The first directory
+/// containing one wins, with `.muxed.yml` preferred over `muxed.yml`.
+pub fn find_local_project(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .flat_map(|dir| LOCAL_PROJECT_FILES.iter().map(move |file| dir.join(file)))
+        .find(|file| file.is_file())
+}
+
 /// A Thin wrapper around the home_dir crate. This is so we can swap the default
 /// dir out during testing.
 #[cfg(not(any(test, doctest)))]
@@ -124,10 +200,38 @@ pub fn homedir() -> Option<PathBuf> {
     Some(std::env::temp_dir())
 }
 
+/// Expands a given path, replacing a leading `~` with the user's home directory if present.
+///
+/// If the path starts with `~`, this function attempts to resolve it to the current user's
+/// home directory and append any additional subpaths. If the path does not begin with `~`,
+/// it is returned unchanged. Returns `None` if home directory resolution fails.
+pub fn expand_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+
+    match components.next() {
+        Some(Component::Normal(os_str)) if os_str == "~" => {
+            if let Some(home) = homedir() {
+                let mut result = home;
+
+                for c in components {
+                    result.push(c.as_os_str());
+                }
+
+                Some(result)
+            } else {
+                None
+            }
+        }
+        _ => Some(path.to_path_buf()),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rand_names;
     use std::env::temp_dir;
+    use std::fs;
 
     #[test]
     fn expects_tmp_as_default_homedir() {
@@ -194,4 +298,113 @@ mod test {
             temp_dir().join(".muxed/custom_template.yml")
         )
     }
+
+    #[test]
+    fn finds_local_project_in_a_parent_directory() {
+        let root = rand_names::project_path();
+        let nested = root.join("src").join("bin");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("muxed.yml"), "windows: ['editor']").unwrap();
+
+        let found = find_local_project(&nested);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(found, Some(root.join("muxed.yml")));
+    }
+
+    #[test]
+    fn prefers_hidden_local_project_file() {
+        let root = rand_names::project_path();
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("muxed.yml"), "windows: ['editor']").unwrap();
+        fs::write(root.join(".muxed.yml"), "windows: ['editor']").unwrap();
+
+        let found = find_local_project(&root);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(found, Some(root.join(".muxed.yml")));
+    }
+
+    #[test]
+    fn expects_local_project_named_after_its_directory() {
+        let project_paths =
+            ProjectPaths::local(temp_dir(), PathBuf::from("/src/billing/.muxed.yml"));
+
+        assert_eq!(
+            project_paths.project_directory,
+            PathBuf::from("/src/billing")
+        );
+        assert_eq!(project_paths.local_name(), Some("billing"));
+    }
+
+    #[test]
+    fn expects_named_project_to_skip_discovery() {
+        let args = Args {
+            arg_project: "projectname".to_string(),
+            ..Default::default()
+        };
+        let project_paths = ProjectPaths::resolve(&args).unwrap();
+
+        assert!(!project_paths.local);
+        assert_eq!(project_paths.local_name(), None);
+    }
+
+    #[test]
+    fn expects_a_var_in_place_of_the_project_to_load_the_local_one() {
+        let root = rand_names::project_path();
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(".muxed.yml"), "windows: ['editor']").unwrap();
+
+        let args = Args {
+            arg_project: "service=billing".to_string(),
+            ..Default::default()
+        };
+        let project_paths = ProjectPaths::resolve_in(&args, &root);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(project_paths.unwrap().project_file, root.join(".muxed.yml"));
+        assert_eq!(args.vars(), vec!["service=billing"]);
+    }
+
+    #[cfg(test)]
+    mod expand_path_tests {
+        use super::*;
+        use std::path::PathBuf;
+
+        fn mock_home() -> PathBuf {
+            // Use the actual homedir, but you can modify this for more control in the future
+            homedir().expect("Homedir should exist for test")
+        }
+
+        #[test]
+        fn expands_tilde_to_home() {
+            let input = PathBuf::from("~");
+            let result = expand_path(&input);
+            assert_eq!(result, Some(mock_home()));
+        }
+
+        #[test]
+        fn expands_tilde_prefix_with_subdirs() {
+            let input = PathBuf::from("~/some/folder");
+            let result = expand_path(&input);
+            let mut expected = mock_home();
+            expected.push("some");
+            expected.push("folder");
+            assert_eq!(result, Some(expected));
+        }
+
+        #[test]
+        fn leaves_absolute_path_unmodified() {
+            let input = PathBuf::from("/usr/local/bin");
+            let result = expand_path(&input);
+            assert_eq!(result, Some(PathBuf::from("/usr/local/bin")));
+        }
+
+        #[test]
+        fn leaves_relative_path_unmodified() {
+            let input = PathBuf::from("foo/bar/baz");
+            let result = expand_path(&input);
+            assert_eq!(result, Some(PathBuf::from("foo/bar/baz")));
+        }
+    }
 }
@@ -0,0 +1,337 @@
+//! Window layouts. A layout is either one of tmux's named presets or a custom
+//! layout string, like the ones `list-windows` prints, made of a checksum and
+//! a description of the panes: `d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}`.
+
+use std::fmt;
+
+/// The layouts tmux knows by name.
+pub static PRESETS: [&str; 7] = [
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-horizontal-mirrored",
+    "main-vertical",
+    "main-vertical-mirrored",
+    "tiled",
+];
+
+/// Compute the checksum tmux prefixes custom layouts with. This is the same
+/// rotate and add over the layout body that tmux's `layout_checksum` does.
+pub fn checksum(body: &str) -> u16 {
+    body.bytes().fold(0u16, |csum, byte| {
+        let rotated = (csum >> 1) | ((csum & 1) << 15);
+        rotated.wrapping_add(u16::from(byte))
+    })
+}
+
+/// Whether the layout is a preset name.
+pub fn is_preset(layout: &str) -> bool {
+    PRESETS.contains(&layout)
+}
+
+/// Whether tmux would accept the layout: either a preset name, or a custom
+/// layout whose checksum matches its body.
+pub fn is_valid(layout: &str) -> bool {
+    if is_preset(layout) {
+        return true;
+    }
+
+    match layout.split_once(',') {
+        Some((csum, body)) if !csum.is_empty() && csum.len() <= 4 && !body.is_empty() => {
+            u16::from_str_radix(csum, 16).is_ok_and(|csum| csum == checksum(body))
+        }
+        _ => false,
+    }
+}
+
+/// A cell in a custom layout: a pane, or a group of cells laid out left to
+/// right, `{...}`, or top to bottom, `[...]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+    pub kind: CellKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellKind {
+    Pane(Option<u32>),
+    LeftRight(Vec<Cell>),
+    TopBottom(Vec<Cell>),
+}
+
+impl Cell {
+    /// Parse a custom layout, with or without its checksum.
+    pub fn parse(layout: &str) -> Option<Cell> {
+        let body = match layout.split_once(',') {
+            Some((csum, body)) if !csum.contains('x') => body,
+            _ => layout,
+        };
+
+        let (cell, rest) = Cell::parse_cell(body)?;
+        rest.is_empty().then_some(cell)
+    }
+
+    fn parse_cell(input: &str) -> Option<(Cell, &str)> {
+        let (width, rest) = number(input)?;
+        let (height, rest) = number(rest.strip_prefix('x')?)?;
+        let (x, rest) = number(rest.strip_prefix(',')?)?;
+        let (y, rest) = number(rest.strip_prefix(',')?)?;
+
+        let (kind, rest) = match rest.chars().next() {
+            Some(open @ ('{' | '[')) => {
+                let close = if open == '{' { '}' } else { ']' };
+                let mut cells = vec![];
+                let mut rest = &rest[1..];
+
+                loop {
+                    let (cell, after) = Cell::parse_cell(rest)?;
+                    cells.push(cell);
+
+                    match after.chars().next() {
+                        Some(',') => rest = &after[1..],
+                        Some(c) if c == close => {
+                            rest = &after[1..];
+                            break;
+                        }
+                        _ => return None,
+                    }
+                }
+
+                match open {
+                    '{' => (CellKind::LeftRight(cells), rest),
+                    _ => (CellKind::TopBottom(cells), rest),
+                }
+            }
+            // A pane's id only follows a comma when another number comes next.
+            Some(',') if rest[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                match number(&rest[1..]) {
+                    Some((id, after)) if !after.starts_with('x') => {
+                        (CellKind::Pane(Some(id)), after)
+                    }
+                    _ => (CellKind::Pane(None), rest),
+                }
+            }
+            _ => (CellKind::Pane(None), rest),
+        };
+
+        let cell = Cell {
+            width,
+            height,
+            x,
+            y,
+            kind,
+        };
+
+        Some((cell, rest))
+    }
+
+    /// The panes in the layout, in the order tmux numbers them.
+    pub fn panes(&self) -> Vec<&Cell> {
+        match &self.kind {
+            CellKind::Pane(_) => vec![self],
+            CellKind::LeftRight(cells) | CellKind::TopBottom(cells) => {
+                cells.iter().flat_map(Cell::panes).collect()
+            }
+        }
+    }
+
+    /// Drop the last pane, giving its space and the border beside it to the
+    /// cell before it. A group left with one cell is replaced by that cell.
+    fn remove_last(&mut self) -> bool {
+        let (cells, left_right) = match &mut self.kind {
+            CellKind::Pane(_) => return false,
+            CellKind::LeftRight(cells) => (cells, true),
+            CellKind::TopBottom(cells) => (cells, false),
+        };
+
+        let nested = match cells.last_mut() {
+            Some(last) => last.remove_last(),
+            None => return false,
+        };
+        let removed = if nested { None } else { cells.pop() };
+
+        if let (Some(removed), Some(previous)) = (removed, cells.last_mut()) {
+            match left_right {
+                true => previous.grow(true, removed.width + 1),
+                false => previous.grow(false, removed.height + 1),
+            }
+        }
+
+        if cells.len() == 1 {
+            let only = cells.remove(0);
+            self.kind = only.kind;
+        }
+
+        true
+    }
+
+    /// Widen, or heighten, the cell and whatever it holds by `by`.
+    fn grow(&mut self, wide: bool, by: u32) {
+        match wide {
+            true => self.width += by,
+            false => self.height += by,
+        }
+
+        match &mut self.kind {
+            CellKind::Pane(_) => {}
+            CellKind::LeftRight(cells) if wide => {
+                if let Some(last) = cells.last_mut() {
+                    last.grow(wide, by)
+                }
+            }
+            CellKind::TopBottom(cells) if !wide => {
+                if let Some(last) = cells.last_mut() {
+                    last.grow(wide, by)
+                }
+            }
+            CellKind::LeftRight(cells) | CellKind::TopBottom(cells) => {
+                cells.iter_mut().for_each(|cell| cell.grow(wide, by))
+            }
+        }
+    }
+
+    /// The preset closest to the layout's shape.
+    pub fn nearest_preset(&self) -> &'static str {
+        let all_panes = |cells: &[Cell]| cells.iter().all(|c| matches!(c.kind, CellKind::Pane(_)));
+
+        match &self.kind {
+            CellKind::LeftRight(cells) if all_panes(cells) => "even-horizontal",
+            CellKind::LeftRight(_) => "main-vertical",
+            CellKind::TopBottom(cells) if all_panes(cells) => "even-vertical",
+            CellKind::TopBottom(_) => "main-horizontal",
+            CellKind::Pane(_) => "tiled",
+        }
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{},{},{}", self.width, self.height, self.x, self.y)?;
+
+        let (open, close, cells) = match &self.kind {
+            CellKind::Pane(Some(id)) => return write!(f, ",{}", id),
+            CellKind::Pane(None) => return Ok(()),
+            CellKind::LeftRight(cells) => ('{', '}', cells),
+            CellKind::TopBottom(cells) => ('[', ']', cells),
+        };
+
+        let cells: Vec<String> = cells.iter().map(Cell::to_string).collect();
+        write!(f, "{}{}{}", open, cells.join(","), close)
+    }
+}
+
+fn number(input: &str) -> Option<(u32, &str)> {
+    let end = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let value = input[..end].parse().ok()?;
+    Some((value, &input[end..]))
+}
+
+/// Prefix a layout body with its checksum.
+pub fn with_checksum(body: &str) -> String {
+    format!("{:04x},{}", checksum(body), body)
+}
+
+/// Fit a layout to a window with `panes` panes. Presets fit any number of
+/// panes. A custom layout gets a fresh checksum, and loses its last panes when
+/// it has more than the window. One with fewer panes than the window can't be
+/// stretched, so the preset nearest its shape is used instead. Layouts that
+/// don't parse are left for tmux to judge.
+pub fn fit(layout: &str, panes: usize) -> String {
+    let mut cell = match (is_preset(layout), Cell::parse(layout)) {
+        (false, Some(cell)) => cell,
+        _ => return layout.to_string(),
+    };
+
+    let count = cell.panes().len();
+    if count < panes {
+        return cell.nearest_preset().to_string();
+    }
+
+    for _ in panes.max(1)..count {
+        cell.remove_last();
+    }
+
+    with_checksum(&cell.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums_match_tmux() {
+        assert_eq!(checksum("159x48,0,0{79x48,0,0,0,79x48,80,0,1}"), 0xd463);
+    }
+
+    #[test]
+    fn accepts_presets() {
+        assert!(is_valid("main-vertical"));
+        assert!(is_valid("tiled"));
+    }
+
+    #[test]
+    fn accepts_custom_layouts_with_a_matching_checksum() {
+        assert!(is_valid("d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"));
+    }
+
+    #[test]
+    fn rejects_custom_layouts_with_a_wrong_checksum() {
+        assert!(!is_valid("d464,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert!(!is_valid("main_vertical"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("d463,"));
+    }
+
+    static EDITOR_AND_STACK: &str =
+        "4c1a,120x40,0,0{89x40,0,0,0,30x40,90,0[30x20,90,0,1,30x19,90,21,2]}";
+
+    #[test]
+    fn parses_and_prints_layouts_unchanged() {
+        let cell = Cell::parse(EDITOR_AND_STACK).unwrap();
+        assert_eq!(cell.panes().len(), 3);
+        assert_eq!(with_checksum(&cell.to_string()), EDITOR_AND_STACK);
+    }
+
+    #[test]
+    fn fits_a_layout_with_as_many_panes() {
+        assert_eq!(fit(EDITOR_AND_STACK, 3), EDITOR_AND_STACK);
+    }
+
+    #[test]
+    fn rederives_a_stale_checksum() {
+        let stale = EDITOR_AND_STACK.replacen("4c1a", "0000", 1);
+        assert_eq!(fit(&stale, 3), EDITOR_AND_STACK);
+    }
+
+    #[test]
+    fn drops_panes_the_window_doesnt_have() {
+        let fitted = fit(EDITOR_AND_STACK, 2);
+        assert!(is_valid(&fitted));
+        assert_eq!(
+            fitted.split_once(',').unwrap().1,
+            "120x40,0,0{89x40,0,0,0,30x40,90,0,1}"
+        );
+
+        let fitted = fit(EDITOR_AND_STACK, 1);
+        assert_eq!(fitted.split_once(',').unwrap().1, "120x40,0,0,0");
+    }
+
+    #[test]
+    fn falls_back_to_the_nearest_preset() {
+        assert_eq!(fit(EDITOR_AND_STACK, 4), "main-vertical");
+        assert_eq!(
+            fit("d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}", 3),
+            "even-horizontal"
+        );
+        assert_eq!(fit("tiled", 5), "tiled");
+    }
+}
@@ -1,14 +1,23 @@
 mod config;
+pub mod layout;
 pub mod pane;
 mod pre;
 pub mod session;
+mod split;
 mod target;
+pub mod tree;
 pub mod window;
 
 fn is_false(b: &bool) -> bool {
     !b
 }
 
+/// Write a list of tmux keys inline, `[C-c, q]`. Two block lists in a row
+/// under one key come out misindented from serde_saphyr.
+fn flow_keys<S: serde::Serializer>(keys: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&serde_saphyr::FlowSeq(keys), serializer)
+}
+
 pub type Active = bool;
 pub type Layout = String;
 
@@ -16,5 +25,6 @@ pub use config::Config;
 pub use pane::Pane;
 pub use pre::Pre;
 pub use session::Session;
+pub use split::{Direction, Size};
 pub use target::Target;
 pub use window::Window;
@@ -1,5 +1,7 @@
-use crate::tmux::{Active, Target, is_false};
+use crate::tmux::{Active, Direction, Pre, Size, Target, flow_keys, is_false};
+use schemars::{JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::path::PathBuf;
 
 #[derive(Debug, Default, Clone, Serialize)]
@@ -8,27 +10,92 @@ pub struct Pane {
     pub active: Active,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
+    #[serde(
+        default,
+        serialize_with = "flow_keys",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panes: Vec<Pane>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre: Option<Pre>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollback: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Size>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<Direction>,
+    #[serde(
+        default,
+        serialize_with = "flow_keys",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub stop_keys: Vec<String>,
     #[serde(skip_serializing)]
     pub target: Option<Target>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(untagged)]
 enum PaneRepr {
     Str(String),
     Map(PaneInner),
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 struct PaneInner {
+    /// Select this pane once the window is built.
     #[serde(default)]
     active: Option<bool>,
+    /// The command to run in the pane.
     #[serde(default)]
     command: Option<String>,
+    /// tmux keys sent to the pane after its command, like `C-c` then `q`. Key
+    /// names are sent as the keys they name, where the command is typed as it's
+    /// written.
+    #[serde(default)]
+    keys: Vec<String>,
+    /// Split the pane in to more panes. The first takes the pane's place and
+    /// each of the others is split off the one before it. Set the split on
+    /// these panes, this pane's command isn't run.
+    #[serde(default)]
+    panes: Vec<Pane>,
+    /// The directory the pane starts in. Defaults to the window's `path`.
     #[serde(default)]
     path: Option<PathBuf>,
+    /// Commands run in the pane before its own command.
+    #[serde(default)]
+    pre: Option<Pre>,
+    /// A file of the pane's earlier output, printed in the pane before anything
+    /// else runs in it. Written by `muxed snapshot --with-scrollback`.
+    #[serde(default)]
+    scrollback: Option<PathBuf>,
+    /// The size of the pane when it's split off the pane before it, in cells
+    /// like `20` or as a percentage like `30%`.
+    #[serde(default)]
+    size: Option<Size>,
+    /// Which way the pane is split off the pane before it: `horizontal` to
+    /// place it to the right, `vertical` to place it below.
+    #[serde(default)]
+    split: Option<Direction>,
+    /// tmux keys sent to the pane when the project is stopped, like `C-c`.
+    #[serde(default)]
+    stop_keys: Vec<String>,
+}
+
+/// A pane's schema is `PaneRepr`'s, the forms `Deserialize` accepts: a command
+/// string or a map.
+impl JsonSchema for Pane {
+    fn schema_name() -> Cow<'static, str> {
+        "Pane".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        PaneRepr::json_schema(generator)
+    }
 }
 
 impl<'de> Deserialize<'de> for Pane {
@@ -64,13 +131,27 @@ impl<'de> Deserialize<'de> for Pane {
             PaneRepr::Str(cmd) => Ok(Pane {
                 active: false,
                 command: Some(cmd),
+                keys: vec![],
+                panes: vec![],
                 path: None,
+                pre: None,
+                scrollback: None,
+                size: None,
+                split: None,
+                stop_keys: vec![],
                 target: None,
             }),
             PaneRepr::Map(inner) => Ok(Pane {
                 active: inner.active.unwrap_or(false),
                 command: inner.command,
+                keys: inner.keys,
+                panes: inner.panes,
                 path: inner.path,
+                pre: inner.pre,
+                scrollback: inner.scrollback,
+                size: inner.size,
+                split: inner.split,
+                stop_keys: inner.stop_keys,
                 target: None,
             }),
         }
@@ -86,7 +167,7 @@ mod tests {
     fn deserializes_from_string() {
         let yaml = "htop";
         let pane: Pane = serde_saphyr::from_str(yaml).unwrap();
-        assert_eq!(pane.active, false);
+        assert!(!pane.active);
         assert_eq!(pane.command, Some("htop".to_string()));
         assert!(pane.path.is_none());
         assert!(pane.target.is_none());
@@ -100,7 +181,7 @@ command: ls
 path: /tmp
 ";
         let pane: Pane = serde_saphyr::from_str(yaml).unwrap();
-        assert_eq!(pane.active, true);
+        assert!(pane.active);
         assert_eq!(pane.command, Some("ls".to_string()));
         assert_eq!(pane.path.unwrap(), PathBuf::from("/tmp"));
         assert!(pane.target.is_none());
@@ -110,7 +191,7 @@ path: /tmp
     fn deserializes_from_map_partial() {
         let yaml = "command: ls";
         let pane: Pane = serde_saphyr::from_str(yaml).unwrap();
-        assert_eq!(pane.active, false);
+        assert!(!pane.active);
         assert_eq!(pane.command, Some("ls".to_string()));
         assert!(pane.path.is_none());
         assert!(pane.target.is_none());
@@ -120,9 +201,35 @@ path: /tmp
     fn deserializes_empty_map() {
         let yaml = "{}";
         let pane: Pane = serde_saphyr::from_str(yaml).unwrap();
-        assert_eq!(pane.active, false);
+        assert!(!pane.active);
         assert!(pane.command.is_none());
         assert!(pane.path.is_none());
         assert!(pane.target.is_none());
     }
+
+    #[test]
+    fn deserializes_keys() {
+        let yaml = "keys: [C-c, q]";
+        let pane: Pane = serde_saphyr::from_str(yaml).unwrap();
+        assert_eq!(pane.keys, ["C-c", "q"]);
+    }
+
+    #[test]
+    fn roundtrips_key_lists_next_to_other_lists() {
+        let pane = Pane {
+            keys: vec!["Escape".into(), ":q".into()],
+            panes: vec![Pane::default(), Pane::default()],
+            pre: Some(Pre(vec!["nvm use".into()])),
+            stop_keys: vec!["C-c".into()],
+            ..Default::default()
+        };
+
+        let yaml = serde_saphyr::to_string(&pane).unwrap();
+        let parsed: Pane = serde_saphyr::from_str(&yaml).unwrap();
+
+        assert_eq!(parsed.keys, pane.keys);
+        assert_eq!(parsed.panes.len(), 2);
+        assert_eq!(parsed.pre, pane.pre);
+        assert_eq!(parsed.stop_keys, pane.stop_keys);
+    }
 }
@@ -1,4 +1,6 @@
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
 use std::slice::Iter;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -10,6 +12,23 @@ impl Pre {
     }
 }
 
+/// Mirrors the deserializer below: a single command or a list of them.
+impl JsonSchema for Pre {
+    fn schema_name() -> Cow<'static, str> {
+        "Pre".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A command, or a list of commands, to run in order",
+            "anyOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } }
+            ]
+        })
+    }
+}
+
 /// Represents a wrapper type Pre for holding a vector of strings,
 /// with custom deserialization logic supporting both single strings
 /// and lists of strings.
@@ -1,26 +1,53 @@
 use crate::tmux::pane::Pane;
 use crate::tmux::window::Window;
 use crate::tmux::{Config, Pre, Target};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+/// A muxed project file.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Session {
+    /// The tmux session name. Defaults to the project's name.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// The name of a project in the same directory to inherit from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Commands run on the host before the session is created.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pre: Option<Pre>,
+    /// Commands run on the host once the session is built, before attaching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<Pre>,
+    /// Commands run inside the session by `muxed stop`, before it's killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_stop: Option<Pre>,
+    /// Commands run in every window and pane before its own command.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pre_window: Option<Pre>,
+    /// Commands run in every pane a window is split in to, after `pre_window`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_pane: Option<Pre>,
+    /// The directory every window starts in, unless it sets its own `path`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub root: Option<PathBuf>,
+    /// The windows to open, in order.
+    #[serde(default)]
     pub windows: Vec<Window>,
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub target: Option<Target>,
+    /// Create the session without attaching to it.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub daemonize: Option<bool>,
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub config: Option<Config>,
+    /// Values for the `${name}` and `{{name}}` placeholders in the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vars: Option<BTreeMap<String, String>>,
 }
 
 impl Session {
@@ -59,12 +86,82 @@ impl Session {
     }
 
     pub fn pre_window(&self) -> Option<&Pre> {
-        self.pre.as_ref()
+        self.pre_window.as_ref()
+    }
+
+    pub fn pre_pane(&self) -> Option<&Pre> {
+        self.pre_pane.as_ref()
     }
 
     pub fn find_window_by_name(&self, name: &str) -> Option<Window> {
         self.windows.iter().find(|w| w.name == name).cloned()
     }
+
+    /// Merge the session this one `extends` into it. Values set on this
+    /// session win over the parent's:
+    ///
+    /// - Windows are matched by name. A window defined here replaces the
+    ///   parent's window of the same name in the parent's position, and
+    ///   windows the parent doesn't have are appended after the parent's.
+    /// - `root`, `pre_window`, `pre_pane` and `daemonize` are overridden.
+    ///   `name` isn't inherited, so every project extending a base still gets
+    ///   a session of its own.
+    /// - `pre` and `post` lists are concatenated, with the parent's commands
+    ///   first.
+    /// - `on_stop` lists are concatenated, with this session's commands first,
+    ///   so teardown runs in the reverse order of setup.
+    /// - `vars` are merged, with this session's values overriding.
+    pub fn inherit(&mut self, parent: Session) {
+        let mut windows: Vec<Window> = parent
+            .windows
+            .into_iter()
+            .map(|window| self.find_window_by_name(&window.name).unwrap_or(window))
+            .collect();
+
+        for window in self.windows.drain(..) {
+            if !windows.iter().any(|w| w.name == window.name) {
+                windows.push(window);
+            }
+        }
+
+        self.windows = windows;
+        self.root = self.root.take().or(parent.root);
+        self.pre_window = self.pre_window.take().or(parent.pre_window);
+        self.pre_pane = self.pre_pane.take().or(parent.pre_pane);
+        self.daemonize = self.daemonize.take().or(parent.daemonize);
+
+        self.pre = match (parent.pre, self.pre.take()) {
+            (Some(Pre(mut first)), Some(Pre(then))) => {
+                first.extend(then);
+                Some(Pre(first))
+            }
+            (first, then) => then.or(first),
+        };
+
+        self.post = match (parent.post, self.post.take()) {
+            (Some(Pre(mut first)), Some(Pre(then))) => {
+                first.extend(then);
+                Some(Pre(first))
+            }
+            (first, then) => then.or(first),
+        };
+
+        self.on_stop = match (self.on_stop.take(), parent.on_stop) {
+            (Some(Pre(mut first)), Some(Pre(then))) => {
+                first.extend(then);
+                Some(Pre(first))
+            }
+            (first, then) => first.or(then),
+        };
+
+        self.vars = match (parent.vars, self.vars.take()) {
+            (Some(mut vars), Some(overrides)) => {
+                vars.extend(overrides);
+                Some(vars)
+            }
+            (vars, overrides) => overrides.or(vars),
+        };
+    }
 }
 
 #[derive(Debug)]
@@ -236,6 +333,13 @@ fn test_various_windows() {
         windows:
           - editor:
         "#,
+        r#"
+        vars:
+          service: billing
+          port: 8080
+        windows:
+          - '${service}': 'make run PORT={{port}}'
+        "#,
     ];
 
     for yaml in samples {
@@ -243,6 +347,14 @@ fn test_various_windows() {
         assert!(session.is_ok(), "failed on:\n{yaml}\n");
     }
 
+    // extends without windows of its own
+    let child = r#"
+    extends: base
+    root: ~/src
+    "#;
+    let session: Result<Session, _> = serde_saphyr::from_str(child);
+    assert!(session.is_ok(), "failed on:\n{child}\n");
+
     // error case
     let bad = r#"
     windows:
@@ -251,3 +363,115 @@ fn test_various_windows() {
     let session: Result<Session, _> = serde_saphyr::from_str(bad);
     assert!(session.is_err(), "failed on:\n{bad}\n");
 }
+
+#[test]
+fn test_inherit_merges_windows_by_name() {
+    let parent: Session = serde_saphyr::from_str(
+        r#"
+        root: ~/base
+        pre: 'docker compose up -d'
+        pre_window: 'nvm use'
+        windows:
+          - editor: 'vim .'
+          - logs: 'tail -f log/development.log'
+          - shell: ''
+        "#,
+    )
+    .unwrap();
+
+    let mut child: Session = serde_saphyr::from_str(
+        r#"
+        extends: base
+        root: ~/child
+        pre: 'make deps'
+        windows:
+          - logs: 'journalctl -f'
+          - tests: 'cargo watch'
+        "#,
+    )
+    .unwrap();
+
+    child.inherit(parent);
+
+    let names: Vec<&str> = child.windows.iter().map(|w| w.name()).collect();
+    assert_eq!(names, ["editor", "logs", "shell", "tests"]);
+    assert_eq!(child.windows[1].command.as_deref(), Some("journalctl -f"));
+    assert_eq!(child.root, Some(PathBuf::from("~/child")));
+    assert_eq!(child.pre_window, Some(Pre(vec!["nvm use".into()])));
+    assert_eq!(
+        child.pre,
+        Some(Pre(vec!["docker compose up -d".into(), "make deps".into()]))
+    );
+}
+
+#[test]
+fn test_inherit_runs_the_childs_on_stop_first() {
+    let parent: Session = serde_saphyr::from_str(
+        r#"
+        on_stop: 'docker compose down'
+        windows: ['editor']
+        "#,
+    )
+    .unwrap();
+
+    let mut child: Session = serde_saphyr::from_str(
+        r#"
+        extends: base
+        on_stop: 'rm -f tmp/pids/server.pid'
+        windows:
+          - server:
+              panes:
+                - command: 'rails server'
+                  stop_keys: ['C-c']
+        "#,
+    )
+    .unwrap();
+
+    child.inherit(parent);
+
+    assert_eq!(
+        child.on_stop,
+        Some(Pre(vec![
+            "rm -f tmp/pids/server.pid".into(),
+            "docker compose down".into()
+        ]))
+    );
+    assert_eq!(child.windows[1].panes[0].stop_keys, ["C-c"]);
+}
+
+#[test]
+fn test_inherit_keeps_the_base_name_out_of_children() {
+    let base = r#"
+        name: base
+        windows: ['editor']
+        "#;
+
+    let mut api: Session = serde_saphyr::from_str("extends: base\nwindows: ['logs']").unwrap();
+    let mut web: Session = serde_saphyr::from_str("extends: base\nwindows: ['logs']").unwrap();
+
+    api.inherit(serde_saphyr::from_str(base).unwrap());
+    web.inherit(serde_saphyr::from_str(base).unwrap());
+
+    assert_eq!(api.name, None);
+    assert_eq!(web.name, None);
+}
+
+#[test]
+fn test_pre_window_is_not_pre() {
+    let session: Session = serde_saphyr::from_str(
+        r#"
+        pre: 'docker compose up -d'
+        pre_window: 'nvm use'
+        pre_pane: 'clear'
+        windows: ['editor']
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        session.pre(),
+        Some(&Pre(vec!["docker compose up -d".into()]))
+    );
+    assert_eq!(session.pre_window(), Some(&Pre(vec!["nvm use".into()])));
+    assert_eq!(session.pre_pane(), Some(&Pre(vec!["clear".into()])));
+}
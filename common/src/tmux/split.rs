@@ -0,0 +1,161 @@
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Which way a pane is split off the pane before it. `horizontal` places the
+/// new pane to the right, `vertical` places it below, the same as tmux's
+/// `split-window -h` and `-v`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+impl Direction {
+    /// The `split-window` flag for the direction.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Direction::Horizontal => "-h",
+            Direction::Vertical => "-v",
+        }
+    }
+}
+
+/// The size of a new pane, in cells or as a percentage of the pane it's split
+/// off. Passed to `split-window -l`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    Cells(u32),
+    Percent(u8),
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Size::Cells(cells) => write!(f, "{}", cells),
+            Size::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl Serialize for Size {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Size::Cells(cells) => serializer.serialize_u32(*cells),
+            Size::Percent(_) => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+/// Mirrors the deserializer below: a number of cells or a percentage.
+impl JsonSchema for Size {
+    fn schema_name() -> Cow<'static, str> {
+        "Size".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A size in cells, like `20`, or a percentage, like `30%`",
+            "anyOf": [
+                { "type": "integer", "minimum": 1 },
+                { "type": "string", "pattern": "^[0-9]+%?$" }
+            ]
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeRepr {
+    Num(u32),
+    Str(String),
+}
+
+/// A `Size` can be written as a number of cells, `size: 20`, or as a
+/// percentage, `size: 30%`. Sizes of zero and percentages over 100 are
+/// rejected.
+impl<'de> Deserialize<'de> for Size {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let invalid = |value: &str| {
+            serde::de::Error::custom(format!(
+                "`{}` isn't a size, use a number of cells like `20` or a percentage like `30%`",
+                value
+            ))
+        };
+
+        let size = match SizeRepr::deserialize(deserializer)? {
+            SizeRepr::Num(cells) => Size::Cells(cells),
+            SizeRepr::Str(value) => match value.trim().strip_suffix('%') {
+                Some(percent) => percent
+                    .parse()
+                    .ok()
+                    .filter(|percent| *percent <= 100)
+                    .map(Size::Percent)
+                    .ok_or_else(|| invalid(&value))?,
+                None => value
+                    .trim()
+                    .parse()
+                    .map(Size::Cells)
+                    .map_err(|_| invalid(&value))?,
+            },
+        };
+
+        match size {
+            Size::Cells(0) | Size::Percent(0) => Err(invalid(&size.to_string())),
+            size => Ok(size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_cells_and_percentages() {
+        let size: Size = serde_saphyr::from_str("20").unwrap();
+        assert_eq!(size, Size::Cells(20));
+
+        let size: Size = serde_saphyr::from_str("30%").unwrap();
+        assert_eq!(size, Size::Percent(30));
+
+        let size: Size = serde_saphyr::from_str("'15'").unwrap();
+        assert_eq!(size, Size::Cells(15));
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        for yaml in ["0", "0%", "120%", "wide", "-5"] {
+            let size: Result<Size, _> = serde_saphyr::from_str(yaml);
+            assert!(size.is_err(), "accepted {}", yaml);
+        }
+    }
+
+    #[test]
+    fn serializes_back_to_what_was_written() {
+        assert_eq!(
+            serde_saphyr::to_string(&Size::Cells(20)).unwrap().trim(),
+            "20"
+        );
+        assert_eq!(
+            serde_saphyr::to_string(&Size::Percent(30)).unwrap().trim(),
+            "30%"
+        );
+    }
+
+    #[test]
+    fn deserializes_directions() {
+        let direction: Direction = serde_saphyr::from_str("horizontal").unwrap();
+        assert_eq!(direction.flag(), "-h");
+        let direction: Direction = serde_saphyr::from_str("vertical").unwrap();
+        assert_eq!(direction.flag(), "-v");
+    }
+}
@@ -0,0 +1,214 @@
+//! Panes can hold panes of their own, so a window's panes form a tree. tmux
+//! numbers a window's panes by their position in a flat list, and a new pane
+//! is numbered right after the pane it was split off, moving every pane after
+//! it along by one. Both the panes each split targets and the final pane
+//! indices depend on the order the splits are made in, which is worked out
+//! here.
+//!
+//! Each pane is split off the pane before it in the same list. All of a list's
+//! panes are split off before any of them is split in to its own panes, so a
+//! pane is always whole when the next one is split off it.
+
+use crate::tmux::{Pane, Target};
+
+/// Where a pane sits in a window's tree, as its index in each list of panes
+/// from the window's down.
+pub type PanePath = Vec<usize>;
+
+/// A `split-window` made while building a window's panes.
+#[derive(Debug, PartialEq)]
+pub struct SplitStep {
+    /// The position of the pane split off, counting from 0.
+    pub target: usize,
+    /// The pane the split creates.
+    pub pane: PanePath,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PaneTree {
+    /// The splits that build the window's panes, in order.
+    pub splits: Vec<SplitStep>,
+    /// The panes without panes of their own, in the order tmux numbers them.
+    pub order: Vec<PanePath>,
+}
+
+impl PaneTree {
+    pub fn new(panes: &[Pane]) -> PaneTree {
+        let mut tree = PaneTree::default();
+
+        if !panes.is_empty() {
+            tree.order.push(vec![]);
+            tree.expand(panes, vec![]);
+        }
+
+        tree
+    }
+
+    /// Split the pane at `parent` in to `panes`. The first of them takes the
+    /// pane's place, the rest are split off the one before them.
+    fn expand(&mut self, panes: &[Pane], parent: PanePath) {
+        if panes.is_empty() {
+            return;
+        }
+
+        let child = |index: usize| [&parent[..], &[index]].concat();
+
+        if let Some(position) = self.position(&parent) {
+            self.order[position] = child(0);
+        }
+
+        for index in 1..panes.len() {
+            if let Some(position) = self.position(&child(index - 1)) {
+                self.order.insert(position + 1, child(index));
+                self.splits.push(SplitStep {
+                    target: position,
+                    pane: child(index),
+                });
+            }
+        }
+
+        for (index, pane) in panes.iter().enumerate() {
+            self.expand(&pane.panes, child(index));
+        }
+    }
+
+    fn position(&self, path: &[usize]) -> Option<usize> {
+        self.order.iter().position(|p| p == path)
+    }
+}
+
+pub fn pane_at<'a>(panes: &'a [Pane], path: &[usize]) -> Option<&'a Pane> {
+    let (first, rest) = path.split_first()?;
+    let pane = panes.get(*first)?;
+
+    match rest.is_empty() {
+        true => Some(pane),
+        false => pane_at(&pane.panes, rest),
+    }
+}
+
+pub fn pane_at_mut<'a>(panes: &'a mut [Pane], path: &[usize]) -> Option<&'a mut Pane> {
+    let (first, rest) = path.split_first()?;
+    let pane = panes.get_mut(*first)?;
+
+    match rest.is_empty() {
+        true => Some(pane),
+        false => pane_at_mut(&mut pane.panes, rest),
+    }
+}
+
+/// Target every pane in a window's tree. Panes get their position in the
+/// window, offset by `pane_base_index`, and panes holding panes get the
+/// target of their first pane, which took their place.
+pub fn assign_targets(panes: &mut [Pane], window: &Target, pane_base_index: usize) {
+    let tree = PaneTree::new(panes);
+
+    for (position, path) in tree.order.iter().enumerate() {
+        let target = Target::new(
+            window.session.clone(),
+            window.window,
+            Some(position + pane_base_index),
+        );
+
+        let mut path = path.clone();
+        while !path.is_empty() {
+            if let Some(pane) = pane_at_mut(panes, &path) {
+                pane.target = Some(target.clone());
+            }
+
+            if path.pop() != Some(0) {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf() -> Pane {
+        Pane::default()
+    }
+
+    fn split(panes: Vec<Pane>) -> Pane {
+        Pane {
+            panes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flat_panes_split_off_the_last_one() {
+        let tree = PaneTree::new(&[leaf(), leaf(), leaf()]);
+        assert_eq!(
+            tree.splits,
+            vec![
+                SplitStep {
+                    target: 0,
+                    pane: vec![1]
+                },
+                SplitStep {
+                    target: 1,
+                    pane: vec![2]
+                },
+            ]
+        );
+        assert_eq!(tree.order, vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn no_panes_means_no_splits() {
+        assert_eq!(PaneTree::new(&[]), PaneTree::default());
+    }
+
+    #[test]
+    fn nested_panes_are_numbered_after_the_pane_they_split() {
+        // editor | top
+        //        |-----
+        //        | shell
+        // -------------
+        // logs
+        let panes = [split(vec![leaf(), split(vec![leaf(), leaf()])]), leaf()];
+        let tree = PaneTree::new(&panes);
+
+        assert_eq!(
+            tree.splits,
+            vec![
+                SplitStep {
+                    target: 0,
+                    pane: vec![1]
+                },
+                SplitStep {
+                    target: 0,
+                    pane: vec![0, 1]
+                },
+                SplitStep {
+                    target: 1,
+                    pane: vec![0, 1, 1]
+                },
+            ]
+        );
+        assert_eq!(
+            tree.order,
+            vec![vec![0, 0], vec![0, 1, 0], vec![0, 1, 1], vec![1]]
+        );
+    }
+
+    #[test]
+    fn targets_respect_pane_base_index() {
+        let mut panes = vec![leaf(), split(vec![leaf(), leaf()])];
+        assign_targets(&mut panes, &Target::new("muxed", Some(2), None), 1);
+
+        let combined = |path: &[usize]| {
+            pane_at(&panes, path)
+                .and_then(|pane| pane.target.clone())
+                .map(|target| target.combined)
+        };
+
+        assert_eq!(combined(&[0]).as_deref(), Some("muxed:2.1"));
+        assert_eq!(combined(&[1]).as_deref(), Some("muxed:2.2"));
+        assert_eq!(combined(&[1, 0]).as_deref(), Some("muxed:2.2"));
+        assert_eq!(combined(&[1, 1]).as_deref(), Some("muxed:2.3"));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::tmux::pane::Pane;
-use crate::tmux::{Active, Layout, Target, is_false};
+use crate::tmux::tree::{PaneTree, pane_at};
+use crate::tmux::{Active, Layout, Pre, Target, flow_keys, is_false};
+use schemars::{JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Serialize, ser::SerializeMap};
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
@@ -12,6 +15,8 @@ pub struct Window {
     pub name: String,
     pub panes: Vec<Pane>,
     pub path: Option<PathBuf>,
+    pub pre: Option<Pre>,
+    pub stop_keys: Vec<String>,
     pub target: Option<Target>,
 }
 
@@ -21,6 +26,17 @@ impl Window {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// The panes tmux ends up with, in the order it numbers them. Panes that
+    /// are split in to panes of their own are left out for the panes they
+    /// hold.
+    pub fn leaves(&self) -> Vec<&Pane> {
+        PaneTree::new(&self.panes)
+            .order
+            .iter()
+            .filter_map(|path| pane_at(&self.panes, path))
+            .collect()
+    }
 }
 
 /// Helper struct for serializing the inner fields of a Window (everything except name)
@@ -36,6 +52,10 @@ struct WindowInner<'a> {
     panes: &'a Vec<Pane>,
     #[serde(skip_serializing_if = "Option::is_none")]
     path: &'a Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre: &'a Option<Pre>,
+    #[serde(serialize_with = "flow_keys", skip_serializing_if = "Vec::is_empty")]
+    stop_keys: &'a Vec<String>,
 }
 
 impl Serialize for Window {
@@ -58,6 +78,8 @@ impl Serialize for Window {
             layout: &self.layout,
             panes: &self.panes,
             path: &self.path,
+            pre: &self.pre,
+            stop_keys: &self.stop_keys,
         };
 
         let mut map = serializer.serialize_map(Some(1))?;
@@ -66,7 +88,7 @@ impl Serialize for Window {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
 enum WindowRepr {
     // windows: ['vim', 'cargo']
@@ -86,39 +108,77 @@ enum WindowRepr {
     //       layout: main-vertical
     //       panes: [...]
     //   - cargo: ''
+    #[schemars(extend("minProperties" = 1, "maxProperties" = 1))]
     Map(BTreeMap<String, InnerOrString>),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
 enum InnerOrString {
     Str(String),
     Inner(Inner),
 }
 
-#[derive(Debug, Default, Deserialize)]
+/// A window's settings, keyed by its name.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
 struct Inner {
+    /// A tmux layout preset, or a custom layout copied from `tmux list-windows`.
     pub layout: Option<Layout>,
+    /// The panes to split the window in to.
     pub panes: Option<Vec<Pane>>,
+    /// Select this window once the session is built.
     pub active: Option<Active>,
+    /// The directory the window starts in. Defaults to the project's `root`.
     pub path: Option<PathBuf>,
+    /// The command to run in the window.
     pub command: Option<String>,
+    /// Commands run in each of the window's panes before its own command.
+    pub pre: Option<Pre>,
+    /// tmux keys sent to the window's panes when the project is stopped, like
+    /// `C-c`. Panes with `stop_keys` of their own are sent those instead.
+    #[serde(default)]
+    pub stop_keys: Vec<String>,
 }
 
 /// Direct window format with name as a field (legacy format for backward compatibility)
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct DirectWindow {
+    /// The window's name.
     pub name: String,
+    /// A tmux layout preset, or a custom layout copied from `tmux list-windows`.
     #[serde(default)]
     pub layout: Option<Layout>,
+    /// The panes to split the window in to.
     #[serde(default)]
     pub panes: Option<Vec<Pane>>,
+    /// Select this window once the session is built.
     #[serde(default)]
     pub active: Option<Active>,
+    /// The directory the window starts in. Defaults to the project's `root`.
     #[serde(default)]
     pub path: Option<PathBuf>,
+    /// The command to run in the window.
     #[serde(default)]
     pub command: Option<String>,
+    /// Commands run in each of the window's panes before its own command.
+    #[serde(default)]
+    pub pre: Option<Pre>,
+    /// tmux keys sent to the window's panes when the project is stopped, like
+    /// `C-c`. Panes with `stop_keys` of their own are sent those instead.
+    #[serde(default)]
+    pub stop_keys: Vec<String>,
+}
+
+/// A window's schema is `WindowRepr`'s, every shorthand and map form
+/// `Deserialize` accepts.
+impl JsonSchema for Window {
+    fn schema_name() -> Cow<'static, str> {
+        "Window".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        WindowRepr::json_schema(generator)
+    }
 }
 
 impl<'de> Deserialize<'de> for Window {
@@ -129,7 +189,7 @@ impl<'de> Deserialize<'de> for Window {
     /// - An integer (used as the window name, with no command)
     /// - A single-key map, where the key is the window name and the value is either:
     ///   - A string (used as the command)
-    ///   - An object with fields `layout`, `panes`, `active`, `path`, and/or `command`
+    ///   - An object with fields `layout`, `panes`, `active`, `path`, `command` and/or `pre`
     ///
     /// Examples of supported YAML representations:
     /// ```yaml
@@ -168,6 +228,8 @@ impl<'de> Deserialize<'de> for Window {
                 path: None,
                 command: Some(name),
                 panes: vec![],
+                pre: None,
+                stop_keys: vec![],
                 target: None,
             }),
             WindowRepr::Num(n) => Ok(Window {
@@ -177,6 +239,8 @@ impl<'de> Deserialize<'de> for Window {
                 path: None,
                 command: None,
                 panes: vec![],
+                pre: None,
+                stop_keys: vec![],
                 target: None,
             }),
             WindowRepr::Map(map) => {
@@ -201,6 +265,8 @@ impl<'de> Deserialize<'de> for Window {
                         name,
                         panes: vec![],
                         path: None,
+                        pre: None,
+                        stop_keys: vec![],
                         target: None,
                     }),
                     InnerOrString::Inner(inner) => Ok(Window {
@@ -210,6 +276,8 @@ impl<'de> Deserialize<'de> for Window {
                         layout: inner.layout,
                         panes: inner.panes.unwrap_or_default(),
                         path: inner.path,
+                        pre: inner.pre,
+                        stop_keys: inner.stop_keys,
                         target: None,
                     }),
                 }
@@ -221,6 +289,8 @@ impl<'de> Deserialize<'de> for Window {
                 layout: direct.layout,
                 panes: direct.panes.unwrap_or_default(),
                 path: direct.path,
+                pre: direct.pre,
+                stop_keys: direct.stop_keys,
                 target: None,
             }),
         }
@@ -283,7 +353,7 @@ term:
         assert_eq!(window.panes.len(), 2);
         assert_eq!(window.panes[0].command.as_ref().unwrap(), "htop");
         assert_eq!(window.panes[1].command.as_ref().unwrap(), "ranger");
-        assert_eq!(window.active, true);
+        assert!(window.active);
         assert_eq!(window.command.as_ref().unwrap(), "mycmd");
         assert_eq!(window.path.as_ref().unwrap().to_str().unwrap(), "/tmp");
     }
@@ -311,6 +381,8 @@ term:
             panes: vec![],
             command: Some("vim".to_string()),
             path: Some(PathBuf::from("/tmp")),
+            pre: None,
+            stop_keys: vec![],
             target: None,
         };
 
@@ -329,6 +401,8 @@ term:
             panes: vec![],
             command: Some("mycmd".to_string()),
             path: Some(PathBuf::from("/tmp")),
+            pre: Some(Pre(vec!["nvm use".to_string()])),
+            stop_keys: vec!["C-c".to_string()],
             target: None,
         };
 
@@ -340,6 +414,8 @@ term:
         assert_eq!(parsed.layout, window.layout);
         assert_eq!(parsed.command, window.command);
         assert_eq!(parsed.path, window.path);
+        assert_eq!(parsed.pre, window.pre);
+        assert_eq!(parsed.stop_keys, window.stop_keys);
     }
 
     #[test]
@@ -0,0 +1,356 @@
+//! Comparing a project with a running session. The comparison reads from the
+//! project to the session, so anything `added` is only in the running session
+//! and anything `removed` is only in the project file.
+
+use common::tmux::layout::{self, Cell, CellKind};
+use common::tmux::{Session, Window};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A value that differs between the project and the running session.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub project: Option<T>,
+    pub session: Option<T>,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(project: Option<T>, session: Option<T>) -> Option<Change<T>> {
+        (project != session).then_some(Change { project, session })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PaneDiff {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<Change<PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Change<String>>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WindowDiff {
+    Added {
+        name: String,
+        panes: usize,
+    },
+    Removed {
+        name: String,
+        panes: usize,
+    },
+    Changed {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        layout: Option<Change<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        panes: Option<Change<usize>>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pane_changes: Vec<PaneDiff>,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SessionDiff {
+    pub project: String,
+    pub session: String,
+    pub windows: Vec<WindowDiff>,
+}
+
+impl SessionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+}
+
+/// A pane as it ends up running: the directory it's in and its command.
+type Effective = (Option<PathBuf>, Option<String>);
+
+/// Compare an enriched project with the inspected running session. Windows
+/// are matched by name, in order, and panes by position. Layouts are only
+/// compared when the project sets one, since tmux always reports a layout,
+/// and only by the arrangement of the panes, since tmux reports the sizes too.
+pub fn compare(project: &Session, running: &Session) -> Vec<WindowDiff> {
+    let mut unmatched: Vec<&Window> = running.windows.iter().collect();
+    let mut diffs = vec![];
+
+    for window in &project.windows {
+        let planned = effective_panes(window, project.root.as_deref());
+
+        match unmatched.iter().position(|w| w.name == window.name) {
+            Some(position) => {
+                let live = unmatched.remove(position);
+                let running_panes = effective_panes(live, running.root.as_deref());
+
+                let layout =
+                    window
+                        .layout
+                        .as_ref()
+                        .and_then(|layout| match live.layout.as_deref() {
+                            Some(running) if same_layout(layout, running) => None,
+                            running => Some(Change {
+                                project: Some(layout.clone()),
+                                session: running.map(String::from),
+                            }),
+                        });
+                let panes = Change::between(Some(planned.len()), Some(running_panes.len()));
+                let pane_changes = compare_panes(&planned, &running_panes);
+
+                if layout.is_some() || panes.is_some() || !pane_changes.is_empty() {
+                    diffs.push(WindowDiff::Changed {
+                        name: window.name.clone(),
+                        layout,
+                        panes,
+                        pane_changes,
+                    });
+                }
+            }
+            None => diffs.push(WindowDiff::Removed {
+                name: window.name.clone(),
+                panes: planned.len(),
+            }),
+        }
+    }
+
+    diffs.extend(unmatched.into_iter().map(|window| WindowDiff::Added {
+        name: window.name.clone(),
+        panes: window.panes.len().max(1),
+    }));
+
+    diffs
+}
+
+fn compare_panes(project: &[Effective], running: &[Effective]) -> Vec<PaneDiff> {
+    project
+        .iter()
+        .zip(running)
+        .enumerate()
+        .filter_map(|(index, ((path, command), (live_path, live_command)))| {
+            let path = Change::between(path.clone(), live_path.clone());
+            let command = Change::between(command.clone(), live_command.clone());
+
+            (path.is_some() || command.is_some()).then_some(PaneDiff {
+                index,
+                path,
+                command,
+            })
+        })
+        .collect()
+}
+
+/// Whether a running layout is the one the project asks for. A preset matches
+/// when it's the nearest preset to the running layout's shape, ignoring which
+/// side the main pane is on, and a lone pane looks the same in every preset.
+/// A custom layout matches when its panes are split the same way. Layouts that
+/// don't parse are compared as they are.
+fn same_layout(project: &str, running: &str) -> bool {
+    let Some(live) = Cell::parse(running) else {
+        return project == running;
+    };
+
+    if layout::is_preset(project) {
+        let preset = project.trim_end_matches("-mirrored");
+        return live.panes().len() == 1 || live.nearest_preset() == preset;
+    }
+
+    match Cell::parse(project) {
+        Some(planned) => same_arrangement(&planned, &live),
+        None => project == running,
+    }
+}
+
+fn same_arrangement(a: &Cell, b: &Cell) -> bool {
+    let same_cells = |a: &[Cell], b: &[Cell]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_arrangement(a, b))
+    };
+
+    match (&a.kind, &b.kind) {
+        (CellKind::Pane(_), CellKind::Pane(_)) => true,
+        (CellKind::LeftRight(a), CellKind::LeftRight(b))
+        | (CellKind::TopBottom(a), CellKind::TopBottom(b)) => same_cells(a, b),
+        _ => false,
+    }
+}
+
+/// A window without panes still runs one, with the window's path and command.
+/// Empty commands run nothing, so they're the same as no command. Nested panes
+/// are flattened in the order tmux numbers them.
+fn effective_panes(window: &Window, root: Option<&Path>) -> Vec<Effective> {
+    let window_path = window.path.as_deref().or(root);
+    let command = |command: &Option<String>| command.clone().filter(|c| !c.is_empty());
+
+    if window.panes.is_empty() {
+        return vec![(window_path.map(Path::to_path_buf), command(&window.command))];
+    }
+
+    window
+        .leaves()
+        .into_iter()
+        .map(|pane| {
+            let path = pane.path.as_deref().or(window_path);
+            (path.map(Path::to_path_buf), command(&pane.command))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::Pane;
+
+    fn pane(path: &str, command: Option<&str>) -> Pane {
+        Pane {
+            path: Some(PathBuf::from(path)),
+            command: command.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    fn window(name: &str, panes: Vec<Pane>) -> Window {
+        Window {
+            name: name.into(),
+            panes,
+            ..Default::default()
+        }
+    }
+
+    fn session(root: &str, windows: Vec<Window>) -> Session {
+        Session {
+            root: Some(PathBuf::from(root)),
+            windows,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_nothing_when_they_match() {
+        let project = session("/src", vec![window("editor", vec![pane("/src", Some(""))])]);
+        let running = session("/src", vec![window("editor", vec![])]);
+        assert!(compare(&project, &running).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_windows() {
+        let project = session(
+            "/src",
+            vec![window("editor", vec![]), window("git", vec![])],
+        );
+        let running = session(
+            "/src",
+            vec![window("editor", vec![]), window("logs", vec![])],
+        );
+        assert_eq!(
+            compare(&project, &running),
+            vec![
+                WindowDiff::Removed {
+                    name: "git".into(),
+                    panes: 1
+                },
+                WindowDiff::Added {
+                    name: "logs".into(),
+                    panes: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_pane_counts_paths_and_commands() {
+        let project = session(
+            "/src",
+            vec![window(
+                "editor",
+                vec![pane("/src", Some("vim")), pane("/src/app", None)],
+            )],
+        );
+        let running = session(
+            "/src",
+            vec![window(
+                "editor",
+                vec![
+                    pane("/src", Some("vim")),
+                    pane("/tmp", Some("htop")),
+                    pane("/src", None),
+                ],
+            )],
+        );
+
+        assert_eq!(
+            compare(&project, &running),
+            vec![WindowDiff::Changed {
+                name: "editor".into(),
+                layout: None,
+                panes: Some(Change {
+                    project: Some(2),
+                    session: Some(3)
+                }),
+                pane_changes: vec![PaneDiff {
+                    index: 1,
+                    path: Some(Change {
+                        project: Some(PathBuf::from("/src/app")),
+                        session: Some(PathBuf::from("/tmp")),
+                    }),
+                    command: Some(Change {
+                        project: None,
+                        session: Some("htop".into()),
+                    }),
+                }],
+            }]
+        );
+    }
+
+    fn with_layout(layout: &str) -> Window {
+        let mut window = window("editor", vec![pane("/src", None), pane("/src", None)]);
+        window.layout = Some(layout.into());
+        window
+    }
+
+    fn layout_change(project: &str, running: &str) -> Option<Change<String>> {
+        let project = session("/src", vec![with_layout(project)]);
+        let running = session("/src", vec![with_layout(running)]);
+
+        match compare(&project, &running).pop() {
+            Some(WindowDiff::Changed { layout, .. }) => layout,
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn only_compares_layouts_the_project_sets() {
+        let mut live = window("editor", vec![]);
+        live.layout = Some("d463,159x48,0,0".into());
+        let running = session("/src", vec![live]);
+
+        let project = session("/src", vec![window("editor", vec![])]);
+        assert!(compare(&project, &running).is_empty());
+
+        let mut tiled = window("editor", vec![]);
+        tiled.layout = Some("tiled".into());
+        let project = session("/src", vec![tiled]);
+        assert!(compare(&project, &running).is_empty());
+    }
+
+    #[test]
+    fn compares_presets_by_the_running_layouts_shape() {
+        let side_by_side = "0d1e,159x48,0,0{79x48,0,0,0,79x48,80,0,1}";
+        assert_eq!(layout_change("even-horizontal", side_by_side), None);
+        assert_eq!(
+            layout_change("even-vertical", side_by_side),
+            Some(Change {
+                project: Some("even-vertical".into()),
+                session: Some(side_by_side.into()),
+            })
+        );
+    }
+
+    #[test]
+    fn compares_custom_layouts_by_their_arrangement() {
+        let planned = "aaaa,159x48,0,0{79x48,0,0,0,79x48,80,0,1}";
+        let resized = "bbbb,200x60,0,0{50x60,0,0,3,149x60,51,0,4}";
+        let stacked = "cccc,159x48,0,0[159x24,0,0,0,159x23,0,25,1]";
+
+        assert_eq!(layout_change(planned, resized), None);
+        assert!(layout_change(planned, stacked).is_some());
+    }
+}
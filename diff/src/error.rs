@@ -0,0 +1,44 @@
+use load::error::LoadError;
+use snapshot::error::SnapshotError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DiffError {
+    Json(serde_json::Error),
+    Load(LoadError),
+    NotRunning(String),
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffError::Json(e) => write!(f, "Couldn't write the diff: {}", e),
+            DiffError::Load(e) => write!(f, "{}", e),
+            DiffError::NotRunning(session) => {
+                write!(f, "There's no running tmux session named `{}`", session)
+            }
+            DiffError::Snapshot(e) => write!(f, "Couldn't inspect the running session: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+impl From<serde_json::Error> for DiffError {
+    fn from(err: serde_json::Error) -> DiffError {
+        DiffError::Json(err)
+    }
+}
+
+impl From<LoadError> for DiffError {
+    fn from(err: LoadError) -> DiffError {
+        DiffError::Load(err)
+    }
+}
+
+impl From<SnapshotError> for DiffError {
+    fn from(err: SnapshotError) -> DiffError {
+        DiffError::Snapshot(err)
+    }
+}
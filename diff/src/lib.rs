@@ -0,0 +1,200 @@
+//! Muxeddiff. Compares a project file with its running tmux session.
+mod compare;
+mod error;
+
+extern crate common;
+extern crate load;
+extern crate snapshot;
+
+use crate::compare::{Change, SessionDiff, WindowDiff, compare};
+use crate::error::DiffError;
+use common::args::Args;
+use load::tmux::has_session;
+
+static NONE: &str = "(none)";
+
+/// The main execution method.
+/// Reads the project the way `load` would, inspects the running session, and
+/// prints what differs between them as a tree, or as JSON with `--json`. The
+/// session defaults to the project's name and can be picked with `-t`.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed diff my_project
+/// $ ./muxed diff my_project -t other_session --json
+/// ```
+pub fn diff(args: Args) -> Result<(), DiffError> {
+    let (project, _) = load::plan(&args, true)?;
+    let session_name = args.flag_t.as_deref().unwrap_or(project.name());
+
+    if !has_session(session_name) {
+        return Err(DiffError::NotRunning(session_name.to_string()));
+    }
+
+    let running = snapshot::inspect(session_name)?;
+    let diff = SessionDiff {
+        project: project.paths().project_file.display().to_string(),
+        session: session_name.to_string(),
+        windows: compare(project.session(), &running),
+    };
+
+    if args.flag_json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{}", to_tree(&diff));
+    }
+
+    Ok(())
+}
+
+/// Render the diff as a tree. `+` marks windows only in the running session,
+/// `-` windows only in the project, and `~` windows in both that differ.
+fn to_tree(diff: &SessionDiff) -> String {
+    let mut lines = vec![format!("{} -> {}", diff.project, diff.session)];
+
+    if diff.is_empty() {
+        lines.push("No differences".to_string());
+    }
+
+    for window in &diff.windows {
+        match window {
+            WindowDiff::Added { name, panes } => {
+                lines.push(format!("+ {} ({})", name, pane_count(*panes)));
+            }
+            WindowDiff::Removed { name, panes } => {
+                lines.push(format!("- {} ({})", name, pane_count(*panes)));
+            }
+            WindowDiff::Changed {
+                name,
+                layout,
+                panes,
+                pane_changes,
+            } => {
+                lines.push(format!("~ {}", name));
+
+                if let Some(layout) = layout {
+                    lines.push(format!("    layout: {}", change(layout, |l| l.clone())));
+                }
+
+                if let Some(panes) = panes {
+                    lines.push(format!("    panes: {}", change(panes, |p| p.to_string())));
+                }
+
+                for pane in pane_changes {
+                    lines.push(format!("    pane {}", pane.index));
+
+                    if let Some(path) = &pane.path {
+                        lines.push(format!(
+                            "        path: {}",
+                            change(path, |p| p.display().to_string())
+                        ));
+                    }
+
+                    if let Some(command) = &pane.command {
+                        lines.push(format!(
+                            "        command: {}",
+                            change(command, |c| c.clone())
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn change<T>(change: &Change<T>, display: impl Fn(&T) -> String) -> String {
+    let side = |value: &Option<T>| match value {
+        Some(value) => display(value),
+        None => NONE.to_string(),
+    };
+
+    format!("{} -> {}", side(&change.project), side(&change.session))
+}
+
+fn pane_count(panes: usize) -> String {
+    match panes {
+        1 => "1 pane".to_string(),
+        count => format!("{} panes", count),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compare::PaneDiff;
+    use std::path::PathBuf;
+
+    fn diff(windows: Vec<WindowDiff>) -> SessionDiff {
+        SessionDiff {
+            project: "/home/muxed/.muxed/work.yml".into(),
+            session: "work".into(),
+            windows,
+        }
+    }
+
+    #[test]
+    fn says_when_there_are_no_differences() {
+        assert_eq!(
+            to_tree(&diff(vec![])),
+            "/home/muxed/.muxed/work.yml -> work\nNo differences\n"
+        );
+    }
+
+    #[test]
+    fn renders_a_tree() {
+        let tree = to_tree(&diff(vec![
+            WindowDiff::Changed {
+                name: "editor".into(),
+                layout: None,
+                panes: Some(Change {
+                    project: Some(2),
+                    session: Some(1),
+                }),
+                pane_changes: vec![PaneDiff {
+                    index: 0,
+                    path: Some(Change {
+                        project: Some(PathBuf::from("/src")),
+                        session: Some(PathBuf::from("/tmp")),
+                    }),
+                    command: Some(Change {
+                        project: Some("vim".into()),
+                        session: None,
+                    }),
+                }],
+            },
+            WindowDiff::Added {
+                name: "logs".into(),
+                panes: 2,
+            },
+        ]));
+
+        assert_eq!(
+            tree,
+            "/home/muxed/.muxed/work.yml -> work
+~ editor
+    panes: 2 -> 1
+    pane 0
+        path: /src -> /tmp
+        command: vim -> (none)
++ logs (2 panes)
+"
+        );
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let json = serde_json::to_value(diff(vec![WindowDiff::Removed {
+            name: "git".into(),
+            panes: 1,
+        }]))
+        .unwrap();
+
+        assert_eq!(json["windows"][0]["status"], "removed");
+        assert_eq!(json["windows"][0]["name"], "git");
+    }
+}
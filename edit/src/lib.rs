@@ -1,33 +1,42 @@
 //! Muxednew. A Muxed project Template Generator
 extern crate common;
-extern crate libc;
 
 use common::args::Args;
 
 use common::error::CommonError;
 use common::project_paths::ProjectPaths;
-use libc::system;
-use std::ffi::CString;
 use std::fmt::Debug;
-use std::{fmt, io};
+use std::process::{Command, ExitStatus};
+use std::{env, fmt, io};
 
+/// Open the project file in `$EDITOR`. The editor can be given with its own
+/// args, like `code --wait`, which are split on whitespace. The project file
+/// is passed as a single arg, so paths with spaces open as they are.
 pub fn edit(args: Args) -> Result<(), EditError> {
     let project_paths = ProjectPaths::try_from(&args)?;
-    let command = format!("{} {}", "$EDITOR", project_paths.project_file.display());
-    let system_call = CString::new(command).map_err(|_| EditError::SysCall)?;
+    let editor = env::var("EDITOR").unwrap_or_default();
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or(EditError::NoEditor)?;
 
-    unsafe {
-        system(system_call.as_ptr());
-    };
+    let status = Command::new(program)
+        .args(words)
+        .arg(&project_paths.project_file)
+        .status()
+        .map_err(|e| EditError::Spawn(program.to_string(), e))?;
 
-    Ok(())
+    match status.success() {
+        true => Ok(()),
+        false => Err(EditError::Editor(editor.clone(), status)),
+    }
 }
 
 #[derive(Debug)]
 pub enum EditError {
     Common(CommonError),
     Io(io::Error),
-    SysCall,
+    Editor(String, ExitStatus),
+    NoEditor,
+    Spawn(String, io::Error),
 }
 
 impl std::error::Error for EditError {}
@@ -37,7 +46,13 @@ impl fmt::Display for EditError {
         match self {
             EditError::Io(e) => write!(f, "IO error: {}", e),
             EditError::Common(e) => write!(f, "{}", e),
-            EditError::SysCall => write!(f, "Couldn't create the editor open command"),
+            EditError::Editor(editor, status) => {
+                write!(f, "The editor `{}` exited with {}", editor, status)
+            }
+            EditError::NoEditor => write!(f, "Set `$EDITOR` to the editor to open projects in"),
+            EditError::Spawn(program, e) => {
+                write!(f, "Couldn't start the editor `{}`: {}", program, e)
+            }
         }
     }
 }
@@ -0,0 +1,29 @@
+use load::error::LoadError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Load(LoadError),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Load(e) => write!(f, "{}", e),
+            ExportError::UnsupportedFormat(format) => write!(
+                f,
+                "The export format `{}` isn't supported. Supported formats: sh",
+                format
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<LoadError> for ExportError {
+    fn from(err: LoadError) -> ExportError {
+        ExportError::Load(err)
+    }
+}
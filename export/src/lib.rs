@@ -0,0 +1,281 @@
+//! Muxedexport. Turns a Muxed project into a standalone shell script.
+mod error;
+
+extern crate common;
+extern crate load;
+
+use crate::error::ExportError;
+use common::args::Args;
+use common::project_paths::homedir;
+use common::tmux::{Config, Target};
+use load::command::{Attach, Commands, SwitchClient};
+use load::shell::{quote, render};
+use load::tmux::TMUX_NAME;
+use std::path::Path;
+
+static SH_FORMAT: &str = "sh";
+static INDENT: &str = "    ";
+
+/// The main execution method.
+/// Plans the project the same way `load` would and prints it as a POSIX shell
+/// script. The script only builds the session when it isn't already running
+/// and finishes by attaching, or switching the client when run inside tmux.
+/// Paths in the home directory are written relative to `$HOME`, and windows
+/// and panes are numbered from the `base-index` and `pane-base-index` of the
+/// tmux the script runs with, so it works for other users too.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed export --format sh my_project > my_project.sh
+/// ```
+pub fn export(args: Args) -> Result<(), ExportError> {
+    let format = args.flag_format.as_deref().unwrap_or(SH_FORMAT);
+    if format != SH_FORMAT {
+        return Err(ExportError::UnsupportedFormat(format.to_string()));
+    }
+
+    // Always plan daemonized. Attaching is decided by the script at runtime.
+    let (project, commands) = load::plan(&args, true)?;
+
+    let config = project.session().config.clone().unwrap_or_default();
+    let home = homedir();
+    let script = to_sh(
+        project.name(),
+        &commands,
+        &config,
+        home.as_deref(),
+        !args.flag_d,
+    );
+    print!("{}", script);
+
+    Ok(())
+}
+
+fn to_sh(
+    name: &str,
+    commands: &[Commands],
+    config: &Config,
+    home: Option<&Path>,
+    attach: bool,
+) -> String {
+    let mut script = vec![
+        "#!/bin/sh".to_string(),
+        format!(
+            "# Generated by `muxed export` for the session {}",
+            quote(name)
+        ),
+        "set -e".to_string(),
+        String::new(),
+        format!(
+            "if ! {} has-session -t {} 2>/dev/null; then",
+            TMUX_NAME,
+            quote(name)
+        ),
+    ];
+
+    script.push(format!(
+        "{}base_index=$({} start-server \\; show-options -gv base-index)",
+        INDENT, TMUX_NAME
+    ));
+    script.push(format!(
+        "{}pane_base_index=$({} start-server \\; show-options -gwv pane-base-index)",
+        INDENT, TMUX_NAME
+    ));
+
+    script.extend(
+        commands
+            .iter()
+            .map(|command| format!("{}{}", INDENT, portable(command, config, home))),
+    );
+    script.push("fi".to_string());
+
+    if attach {
+        let switch: Commands = SwitchClient::new(name).into();
+        let attach: Commands = Attach::new(Target::new(name, None, None), None).into();
+
+        script.push(String::new());
+        script.push("if [ -n \"$TMUX\" ]; then".to_string());
+        script.push(format!("{}{}", INDENT, render(&switch)));
+        script.push("else".to_string());
+        script.push(format!("{}{}", INDENT, render(&attach)));
+        script.push("fi".to_string());
+    }
+
+    script.join("\n") + "\n"
+}
+
+/// Render a command the way `load::shell::render` does, with its window and
+/// pane numbers read from the script's variables and the home directory in
+/// its paths replaced with `$HOME`.
+fn portable(command: &Commands, config: &Config, home: Option<&Path>) -> String {
+    if matches!(command, Commands::Pre(_)) {
+        return render(command);
+    }
+
+    let target = command.target().filter(|target| target.window.is_some());
+    let mut parts = vec![TMUX_NAME.to_string()];
+
+    for arg in command.as_trait().args() {
+        match target {
+            Some(target) if arg == target.combined => parts.push(indexed(target, config)),
+            _ => parts.push(home_relative(arg, home)),
+        }
+    }
+
+    parts.join(" ")
+}
+
+/// A window or pane target with its numbers offset from `$base_index` and
+/// `$pane_base_index` instead of the ones it was planned with.
+fn indexed(target: &Target, config: &Config) -> String {
+    let offset = |variable: &str, index: usize, base: usize| match index.saturating_sub(base) {
+        0 => format!("${}", variable),
+        offset => format!("$(({} + {}))", variable, offset),
+    };
+
+    let mut indexes = vec![];
+    if let Some(window) = target.window {
+        indexes.push(offset("base_index", window, config.base_index));
+    }
+    if let Some(pane) = target.pane {
+        indexes.push(offset("pane_base_index", pane, config.pane_base_index));
+    }
+
+    format!(
+        "{}{}",
+        quote(&format!("{}:", target.session)),
+        indexes.join(".")
+    )
+}
+
+/// Quote an argument with every path in the home directory written relative
+/// to `$HOME`, which stays outside the quotes so the shell expands it.
+fn home_relative(arg: &str, home: Option<&Path>) -> String {
+    let Some(home) = home.and_then(Path::to_str).filter(|home| home.len() > 1) else {
+        return quote(arg).into_owned();
+    };
+
+    let mut quoted = String::new();
+    let mut literal = 0;
+
+    for (start, _) in arg.match_indices(home) {
+        let end = start + home.len();
+        let starts_path = start == 0 || arg[..start].ends_with([' ', '=', ':']);
+        let ends_path = end == arg.len() || arg[end..].starts_with(['/', ' ', ':']);
+
+        if !(starts_path && ends_path) {
+            continue;
+        }
+
+        if start > literal {
+            quoted.push_str(&quote(&arg[literal..start]));
+        }
+        quoted.push_str("\"$HOME\"");
+        literal = end;
+    }
+
+    if literal < arg.len() || quoted.is_empty() {
+        quoted.push_str(&quote(&arg[literal..]));
+    }
+
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use load::command::{Pre, SendKeys, Session, Split};
+    use std::path::PathBuf;
+
+    fn commands() -> Vec<Commands> {
+        vec![
+            Pre::new("touch /tmp/muxed".into()).into(),
+            Session::new("my project", "editor", Some(PathBuf::from("/home/me/src"))).into(),
+            SendKeys::new(
+                Target::new("my project", Some(0), None),
+                "echo 'it works'; ls".into(),
+            )
+            .into(),
+        ]
+    }
+
+    fn sh(commands: &[Commands], attach: bool) -> String {
+        to_sh(
+            "my project",
+            commands,
+            &Config::default(),
+            Some(Path::new("/home/me")),
+            attach,
+        )
+    }
+
+    #[test]
+    fn guards_the_build_with_has_session() {
+        let script = sh(&commands(), true);
+        assert!(script.contains("if ! tmux has-session -t 'my project' 2>/dev/null; then"));
+    }
+
+    #[test]
+    fn quotes_send_keys_payloads() {
+        let script = sh(&commands(), true);
+        assert!(
+            script
+                .contains(r"tmux send-keys -t 'my project:'$base_index -l -- 'echo '\''it works'\''; ls' ';' send-keys -t 'my project:'$base_index KPEnter")
+        );
+    }
+
+    #[test]
+    fn writes_home_paths_relative_to_home() {
+        let script = sh(&commands(), true);
+        assert!(script.contains(r#"-c "$HOME"/src"#));
+
+        let home = Some(Path::new("/home/me"));
+        assert_eq!(home_relative("cd /home/me", home), r#"'cd '"$HOME""#);
+        assert_eq!(home_relative("/home/mebbe", home), "/home/mebbe");
+    }
+
+    #[test]
+    fn numbers_windows_and_panes_from_the_running_base_index() {
+        let config = Config {
+            base_index: 1,
+            pane_base_index: 1,
+        };
+        let split: Commands = Split::new(Target::new("muxed", Some(2), Some(1)), None).into();
+        assert_eq!(
+            portable(&split, &config, None),
+            "tmux split-window -t muxed:$((base_index + 1)).$pane_base_index"
+        );
+
+        let script = sh(&commands(), true);
+        assert!(script.contains(r"base_index=$(tmux start-server \; show-options -gv base-index)"));
+    }
+
+    #[test]
+    fn ends_with_attach_or_switch_client() {
+        let script = sh(&commands(), true);
+        assert!(script.contains("tmux switch-client -t 'my project'"));
+        assert!(script.ends_with("tmux attach -t 'my project'\nfi\n"));
+    }
+
+    #[test]
+    fn skips_attaching_when_daemonized() {
+        let script = sh(&commands(), false);
+        assert!(!script.contains("attach"));
+        assert!(!script.contains("switch-client"));
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let args = Args {
+            flag_format: Some("fish".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            export(args),
+            Err(ExportError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -4,9 +4,10 @@ extern crate common;
 
 use common::args::Args;
 use common::first_run::check_first_run;
-use common::project_paths::{CONFIG_EXTENSION, ProjectPaths};
+use common::project_paths::{CONFIG_EXTENSION, ProjectPaths, find_local_project};
 
 use crate::error::ListError;
+use std::env;
 use std::path::PathBuf;
 
 pub fn list(args: Args) -> Result<(), ListError> {
@@ -30,7 +31,8 @@ pub fn list(args: Args) -> Result<(), ListError> {
 
     projects.sort();
 
-    let delimiter = if !atty::is(atty::Stream::Stdout) || args.flag_1 {
+    let interactive = atty::is(atty::Stream::Stdout);
+    let delimiter = if !interactive || args.flag_1 {
         "\n"
     } else {
         "\t\t"
@@ -38,5 +40,16 @@ pub fn list(args: Args) -> Result<(), ListError> {
 
     println!("{}", &projects.join(delimiter));
 
+    // Only shown to people. Completions read piped output and can't open a
+    // local project by name.
+    if interactive {
+        let local = env::current_dir()
+            .ok()
+            .and_then(|dir| find_local_project(&dir));
+        if let Some(local) = local {
+            println!("\nLocal project: {}", local.display());
+        }
+    }
+
     Ok(())
 }
@@ -8,7 +8,6 @@ mod test {
     mod list {
         use common::args::Args;
         use common::rand_names;
-        use list;
         use std::fs;
         use std::fs::File;
         use std::path::PathBuf;
@@ -0,0 +1,303 @@
+//! The ways a plan's commands are sent to tmux. `each` runs a tmux process per
+//! command. `batch` chains the commands between `pre` commands and attaching
+//! in to one tmux invocation, separated with `;` the way tmux reads `\;` on the
+//! command line, so a project is built by a handful of processes instead of
+//! dozens. tmux stops a chain at the first command in it that fails.
+//! `control` sends the commands over a control mode client attached to the
+//! session once it's created, and gets tmux's answer to each of them.
+//!
+//! Whichever the backend, a command tmux fails to run is a `Failure`. The
+//! `Policy` decides whether the rest of the plan still runs.
+
+use crate::command::Commands;
+use crate::error::LoadError;
+use crate::tmux;
+use crate::tmux::control::Control;
+use crate::tmux::error::TmuxError;
+use common::DEBUG;
+use std::process::Output;
+
+/// How the commands of a plan are sent to tmux.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Backend {
+    #[default]
+    Batch,
+    Control,
+    Each,
+}
+
+impl Backend {
+    /// The backend named by `--backend`, `batch` when it isn't given.
+    pub fn from_arg(arg: Option<&str>) -> Result<Backend, LoadError> {
+        match arg {
+            None | Some("batch") => Ok(Backend::Batch),
+            Some("control") => Ok(Backend::Control),
+            Some("each") => Ok(Backend::Each),
+            Some(other) => Err(LoadError::UnknownBackend(other.to_string())),
+        }
+    }
+}
+
+/// What happens to the rest of the plan when a command fails.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Stop at the first command that fails.
+    #[default]
+    Abort,
+    /// Run every command, collecting the ones that fail.
+    KeepGoing,
+}
+
+/// A planned command that failed, by its index in the plan.
+#[derive(Debug)]
+pub struct Failure {
+    pub index: usize,
+    pub error: TmuxError,
+}
+
+/// Run the commands in order with the given backend. `session` is the session
+/// the commands build, which the control client attaches to.
+pub fn run(
+    commands: &[Commands],
+    backend: Backend,
+    session: &str,
+    policy: Policy,
+) -> Result<(), Vec<Failure>> {
+    let failures = match backend {
+        Backend::Each => each(commands, policy),
+        Backend::Batch => batch(commands, policy),
+        Backend::Control => control(commands, session, policy),
+    };
+
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(failures),
+    }
+}
+
+fn each(commands: &[Commands], policy: Policy) -> Vec<Failure> {
+    let mut failures = vec![];
+
+    for (index, command) in commands.iter().enumerate() {
+        if let Err(error) = call(command) {
+            failures.push(Failure { index, error });
+            if policy == Policy::Abort {
+                break;
+            }
+        }
+    }
+
+    failures
+}
+
+fn batch(commands: &[Commands], policy: Policy) -> Vec<Failure> {
+    let mut failures = vec![];
+    let mut start = 0;
+
+    while start < commands.len() {
+        // `pre` commands run on the host, and attaching takes over the terminal
+        let alone = |command: &Commands| matches!(command, Commands::Pre(_) | Commands::Attach(_));
+
+        let result = match alone(&commands[start]) {
+            true => call(&commands[start])
+                .map(|()| start + 1)
+                .map_err(|error| Failure {
+                    index: start,
+                    error,
+                }),
+            false => {
+                let end = commands[start..]
+                    .iter()
+                    .position(alone)
+                    .map_or(commands.len(), |length| start + length);
+                chain(&commands[start..end], start).map(|()| end)
+            }
+        };
+
+        match result {
+            Ok(next) => start = next,
+            Err(failure) => {
+                start = failure.index + 1;
+                failures.push(failure);
+                if policy == Policy::Abort {
+                    break;
+                }
+            }
+        }
+    }
+
+    failures
+}
+
+/// Run the commands chained in one tmux call. Each of them is followed by a
+/// `display-message` printing its index, so when tmux stops at one that fails
+/// the last index printed tells which it was. `offset` is the index of the
+/// first of them in the plan.
+fn chain(commands: &[Commands], offset: usize) -> Result<(), Failure> {
+    let indices: Vec<String> = (0..commands.len()).map(|i| i.to_string()).collect();
+    let args = chain_args(commands, &indices);
+
+    if DEBUG.load() {
+        println!("{:?}", &args);
+    };
+
+    let output = tmux::call(&args).map_err(|error| Failure {
+        index: offset,
+        error,
+    })?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let failed = failed_at(&output, commands.len());
+    Err(Failure {
+        index: offset + failed,
+        error: command_failed(&commands[failed].as_trait().args(), &output),
+    })
+}
+
+fn chain_args<'a>(commands: &'a [Commands], indices: &'a [String]) -> Vec<&'a str> {
+    let mut args: Vec<&str> = vec![];
+
+    for (command, index) in commands.iter().zip(indices) {
+        if !args.is_empty() {
+            args.push(";");
+        }
+        args.extend(command.as_trait().args());
+        args.extend([";", "display-message", "-p", index]);
+    }
+
+    args
+}
+
+/// The index of the command a failed chain stopped at, the one after the last
+/// index it printed.
+fn failed_at(output: &Output, length: usize) -> usize {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<usize>().ok())
+        .next_back()
+        .map_or(0, |done| done + 1)
+        .min(length - 1)
+}
+
+/// Run the commands over a control client. The session is created and
+/// attached to, and `pre` commands run, the way `each` runs them, with the
+/// client disconnected. So is `switch-client`, which would switch the control
+/// client instead of the user's. Commands with a newline in them can't be
+/// written as a line, those are run on their own too.
+fn control(commands: &[Commands], session: &str, policy: Policy) -> Vec<Failure> {
+    let mut failures = vec![];
+    let mut client: Option<Control> = None;
+
+    for (index, command) in commands.iter().enumerate() {
+        let args = command.as_trait().args();
+        let alone = match command {
+            Commands::Pre(_) | Commands::Attach(_) | Commands::SwitchClient(_) => true,
+            Commands::Session(_) => client.is_none(),
+            _ => args.iter().any(|arg| arg.contains('\n')),
+        };
+
+        let result = match alone {
+            true => {
+                if let Some(client) = client.take() {
+                    let _ = client.close();
+                }
+                call(command)
+            }
+            false => {
+                if DEBUG.load() {
+                    println!("{:?}", &args);
+                };
+                match client.as_mut() {
+                    Some(client) => client.run(&args).map(|_| ()),
+                    None => Control::attach(session)
+                        .and_then(|attached| client.insert(attached).run(&args).map(|_| ())),
+                }
+            }
+        };
+
+        if let Err(error) = result {
+            failures.push(Failure { index, error });
+            if policy == Policy::Abort {
+                break;
+            }
+        }
+    }
+
+    if let Some(client) = client {
+        let _ = client.close();
+    }
+
+    failures
+}
+
+/// Call a command on its own, failing when tmux exits unsuccessfully. A `pre`
+/// command's status is its own program's and isn't checked.
+fn call(command: &Commands) -> Result<(), TmuxError> {
+    let output = command.as_trait().call()?;
+
+    match matches!(command, Commands::Pre(_)) || output.status.success() {
+        true => Ok(()),
+        false => Err(command_failed(&command.as_trait().args(), &output)),
+    }
+}
+
+fn command_failed(args: &[&str], output: &Output) -> TmuxError {
+    TmuxError::CommandFailed {
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        status: output.status.code(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::{SelectWindow, Session, Window};
+    use common::tmux::Target;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn marks_each_chained_command_with_its_index() {
+        let commands: Vec<Commands> = vec![
+            Session::new("muxed", "editor", None).into(),
+            Window::new("logs", Target::new("muxed", Some(1), None), None).into(),
+            SelectWindow::new(Target::new("muxed", Some(0), None)).into(),
+        ];
+        let indices: Vec<String> = (0..3).map(|i| i.to_string()).collect();
+
+        assert_eq!(
+            chain_args(&commands, &indices).join(" "),
+            "new -d -s muxed -n editor ; display-message -p 0 ; \
+             new-window -t muxed:1 -n logs ; display-message -p 1 ; \
+             select-window -t muxed:0 ; display-message -p 2"
+        );
+    }
+
+    #[test]
+    fn finds_the_command_a_chain_stopped_at() {
+        let output = |stdout: &str| Output {
+            status: ExitStatus::from_raw(1 << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: b"invalid layout: bogus\n".to_vec(),
+        };
+
+        assert_eq!(failed_at(&output(""), 3), 0);
+        assert_eq!(failed_at(&output("0\n1\n"), 3), 2);
+        assert_eq!(failed_at(&output("0\n1\n2\n"), 3), 2);
+    }
+
+    #[test]
+    fn reads_the_backend_arg() {
+        assert_eq!(Backend::from_arg(None).unwrap(), Backend::Batch);
+        assert_eq!(Backend::from_arg(Some("each")).unwrap(), Backend::Each);
+        assert_eq!(
+            Backend::from_arg(Some("control")).unwrap(),
+            Backend::Control
+        );
+        assert!(Backend::from_arg(Some("fast")).is_err());
+    }
+}
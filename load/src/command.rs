@@ -1,6 +1,6 @@
 //! The structures used to manage commands sent over to tmux.
 
-use crate::common::tmux::Target;
+use crate::common::tmux::{Direction, Target};
 use crate::tmux;
 use crate::tmux::error::TmuxError;
 use common::DEBUG;
@@ -111,21 +111,41 @@ impl Command for Window {
 /// `target`: The target window. In the format `{session}:{window}.{paneIndex}`.
 /// `path`: An `Option<PathBuf>` containing a possible root directory passed to the
 /// `-c` arguement.
+/// `direction`: Split side by side with `-h` or one above the other with `-v`.
+/// tmux splits `-v` when it's not set.
+/// `size`: The size of the new pane passed to `-l`. ex `30%`.
 #[derive(Debug, Clone)]
 pub struct Split {
     pub target: Target,
     pub path: Option<PathBuf>,
+    pub direction: Option<Direction>,
+    pub size: Option<String>,
 }
 
 impl Split {
     pub fn new(target: Target, path: Option<PathBuf>) -> Split {
-        Split { target, path }
+        Split {
+            target,
+            path,
+            direction: None,
+            size: None,
+        }
     }
 }
 
 impl Command for Split {
     fn args(&self) -> Vec<&str> {
-        let args: Vec<&str> = vec!["split-window", "-t", &self.target.combined];
+        let mut args: Vec<&str> = vec!["split-window"];
+
+        if let Some(direction) = self.direction.as_ref() {
+            args.push(direction.flag());
+        }
+
+        if let Some(size) = self.size.as_ref() {
+            args.extend(["-l", size]);
+        }
+
+        args.extend(["-t", &self.target.combined]);
 
         match self.path.as_ref().and_then(|p| p.to_str()) {
             Some(path) => [&args[..], &["-c", path]].concat(),
@@ -158,18 +178,25 @@ impl Command for Layout {
 
 /// A generic `SendKeys` command used to send "typed" commands to tmux. This is
 /// used to initialize processes or tasks in specific window. Such as starting log
-/// tails or running servers.
+/// tails or running servers. The command is typed as literal text, so words
+/// like `Enter` or `C-c` aren't read as key names, then Enter is pressed.
 /// target: The target window. In the format `{session}:{window}.{paneIndex}`.
 /// exec: The cli command to be run. ex. `tail -f logs/development.log`.
 #[derive(Debug, Clone)]
 pub struct SendKeys {
     pub target: Target,
     pub exec: String,
+    literal: String,
 }
 
 impl SendKeys {
     pub fn new(target: Target, exec: String) -> SendKeys {
-        SendKeys { target, exec }
+        let literal = escape_separator(&exec);
+        SendKeys {
+            target,
+            exec,
+            literal,
+        }
     }
 }
 
@@ -179,12 +206,52 @@ impl Command for SendKeys {
             "send-keys",
             "-t",
             &self.target.combined,
-            &self.exec,
+            "-l",
+            "--",
+            &self.literal,
+            ";",
+            "send-keys",
+            "-t",
+            &self.target.combined,
             "KPEnter",
         ]
     }
 }
 
+/// tmux reads an argument ending in `;` as the end of a command and drops the
+/// `;`. Escaped as `\;` it's kept instead, and a backslash already in front of
+/// it, like in `-exec rm {} \;`, is kept too.
+fn escape_separator(arg: &str) -> String {
+    match arg.strip_suffix(';') {
+        Some(rest) => format!("{}\\;", rest),
+        None => arg.to_string(),
+    }
+}
+
+/// Used to send tmux key names to a pane as they are, without a trailing
+/// Enter. Such as sending `C-c` to stop a server.
+/// target: The target pane. In the format `{session}:{window}.{paneIndex}`.
+/// keys: The keys to send. ex. `C-c`.
+#[derive(Debug, Clone)]
+pub struct Keys {
+    pub target: Target,
+    pub keys: Vec<String>,
+}
+
+impl Keys {
+    pub fn new(target: Target, keys: Vec<String>) -> Keys {
+        Keys { target, keys }
+    }
+}
+
+impl Command for Keys {
+    fn args(&self) -> Vec<&str> {
+        let args: Vec<&str> = vec!["send-keys", "-t", &self.target.combined];
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        [&args[..], &keys[..]].concat()
+    }
+}
+
 /// Used to attach to the daemonized session.
 /// name: The named session to attach too.
 /// `path`: An `Option<PathBuf>` containing a possible root directory passed to the
@@ -207,18 +274,24 @@ impl Command for Attach {
             println!("{:?}", &self.args());
         };
 
-        tmux::attach(&self.args())
+        let output = tmux::attach(&self.args())?;
+
+        match output.status.success() {
+            true => Ok(output),
+            false => Err(TmuxError::Attach(
+                self.target.combined.clone(),
+                output.status,
+            )),
+        }
     }
 
     fn args(&self) -> Vec<&str> {
         let args: Vec<&str> = vec!["attach", "-t", &self.target.combined];
 
-        let args = match self.root_path.as_ref().and_then(|p| p.to_str()) {
+        match self.root_path.as_ref().and_then(|p| p.to_str()) {
             Some(path) => [&args[..], &["-c", path]].concat(),
             None => args,
-        };
-
-        [&args[..], &[">/dev/null"]].concat()
+        }
     }
 }
 
@@ -281,6 +354,62 @@ impl Command for SwitchClient {
     }
 }
 
+/// Used to kill a session once it's been stopped.
+/// target: The session to kill.
+#[derive(Debug, Clone)]
+pub struct KillSession {
+    pub target: Target,
+}
+
+impl KillSession {
+    pub fn new(name: &str) -> KillSession {
+        KillSession {
+            target: Target::new(name.to_string(), None, None),
+        }
+    }
+}
+
+impl Command for KillSession {
+    fn args(&self) -> Vec<&str> {
+        vec!["kill-session", "-t", &self.target.combined]
+    }
+}
+
+/// Used for executing the `on_stop` option inside a running session. tmux
+/// runs the command through the shell and waits for it to finish.
+/// target: The session the command runs in.
+/// path: The directory the command runs in, usually the session root. tmux
+/// only takes `-c` for `run-shell` from 3.4 on.
+/// exec: The command to execute. ex. `docker compose down`. tmux expands
+/// formats in it, so every `#` is doubled to keep it literal.
+#[derive(Debug, Clone)]
+pub struct RunShell {
+    pub target: Target,
+    pub path: Option<PathBuf>,
+    pub exec: String,
+}
+
+impl RunShell {
+    pub fn new(target: Target, path: Option<PathBuf>, exec: &str) -> RunShell {
+        RunShell {
+            target,
+            path,
+            exec: exec.replace('#', "##"),
+        }
+    }
+}
+
+impl Command for RunShell {
+    fn args(&self) -> Vec<&str> {
+        let args = ["run-shell", "-t", &self.target.combined];
+
+        match self.path.as_ref().and_then(|p| p.to_str()) {
+            Some(path) => [&args[..], &["-c", path], &[&self.exec]].concat(),
+            None => [&args[..], &[&self.exec]].concat(),
+        }
+    }
+}
+
 /// Used for executing the `pre` option to execute commands before building the
 /// tmux session.
 /// exec: The command to execute
@@ -320,8 +449,11 @@ impl Command for Pre {
 #[derive(Debug, Clone)]
 pub enum Commands {
     Attach(Attach),
+    Keys(Keys),
+    KillSession(KillSession),
     Layout(Layout),
     Pre(Pre),
+    RunShell(RunShell),
     SelectPane(SelectPane),
     SelectWindow(SelectWindow),
     SendKeys(SendKeys),
@@ -335,8 +467,11 @@ impl Commands {
     pub fn as_trait(&self) -> &dyn Command {
         match self {
             Commands::Attach(c) => c,
+            Commands::Keys(c) => c,
+            Commands::KillSession(c) => c,
             Commands::Layout(c) => c,
             Commands::Pre(c) => c,
+            Commands::RunShell(c) => c,
             Commands::SelectPane(c) => c,
             Commands::SelectWindow(c) => c,
             Commands::SendKeys(c) => c,
@@ -346,6 +481,26 @@ impl Commands {
             Commands::Window(c) => c,
         }
     }
+
+    /// The session, window or pane the command acts on. `None` for `Pre`,
+    /// which runs on the host.
+    pub fn target(&self) -> Option<&Target> {
+        match self {
+            Commands::Attach(c) => Some(&c.target),
+            Commands::Keys(c) => Some(&c.target),
+            Commands::KillSession(c) => Some(&c.target),
+            Commands::Layout(c) => Some(&c.target),
+            Commands::Pre(_) => None,
+            Commands::RunShell(c) => Some(&c.target),
+            Commands::SelectPane(c) => Some(&c.target),
+            Commands::SelectWindow(c) => Some(&c.target),
+            Commands::SendKeys(c) => Some(&c.target),
+            Commands::Session(c) => Some(&c.target),
+            Commands::Split(c) => Some(&c.target),
+            Commands::SwitchClient(c) => Some(&c.name),
+            Commands::Window(c) => Some(&c.target),
+        }
+    }
 }
 
 impl From<Attach> for Commands {
@@ -354,6 +509,18 @@ impl From<Attach> for Commands {
     }
 }
 
+impl From<Keys> for Commands {
+    fn from(command: Keys) -> Self {
+        Commands::Keys(command)
+    }
+}
+
+impl From<KillSession> for Commands {
+    fn from(command: KillSession) -> Self {
+        Commands::KillSession(command)
+    }
+}
+
 impl From<Layout> for Commands {
     fn from(command: Layout) -> Self {
         Commands::Layout(command)
@@ -366,6 +533,12 @@ impl From<Pre> for Commands {
     }
 }
 
+impl From<RunShell> for Commands {
+    fn from(command: RunShell) -> Self {
+        Commands::RunShell(command)
+    }
+}
+
 impl From<SelectPane> for Commands {
     fn from(command: SelectPane) -> Self {
         Commands::SelectPane(command)
@@ -407,3 +580,84 @@ impl From<Window> for Commands {
         Commands::Window(command)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn send_keys(exec: &str) -> SendKeys {
+        SendKeys::new(Target::new("muxed", Some(1), None), exec.into())
+    }
+
+    #[test]
+    fn sends_commands_as_literal_text_then_enter() {
+        assert_eq!(
+            send_keys("vim .").args(),
+            vec![
+                "send-keys",
+                "-t",
+                "muxed:1",
+                "-l",
+                "--",
+                "vim .",
+                ";",
+                "send-keys",
+                "-t",
+                "muxed:1",
+                "KPEnter",
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_shell_commands_in_the_root_with_formats_escaped() {
+        let run = RunShell::new(
+            Target::new("muxed", None, None),
+            Some(PathBuf::from("/src/app")),
+            "echo '#{session_name}' # done",
+        );
+        assert_eq!(
+            run.args(),
+            vec![
+                "run-shell",
+                "-t",
+                "muxed",
+                "-c",
+                "/src/app",
+                "echo '##{session_name}' ## done",
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_key_names_and_flags_as_text() {
+        for exec in ["Enter", "C-c", "-n 5", "--help"] {
+            assert_eq!(send_keys(exec).args()[3..6], ["-l", "--", exec]);
+        }
+    }
+
+    #[test]
+    fn escapes_a_trailing_semicolon() {
+        assert_eq!(
+            send_keys("make; make test;").args()[5],
+            r"make; make test\;"
+        );
+        assert_eq!(
+            send_keys(r"find . -exec rm {} \;").args()[5],
+            r"find . -exec rm {} \\;"
+        );
+        assert_eq!(send_keys("echo 'a;b'").args()[5], "echo 'a;b'");
+    }
+
+    #[test]
+    fn sends_keys_by_name() {
+        let keys = Keys::new(
+            Target::new("muxed", Some(1), Some(0)),
+            vec!["C-c".into(), "q".into()],
+        );
+        assert_eq!(
+            keys.args(),
+            vec!["send-keys", "-t", "muxed:1.0", "C-c", "q"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::interpreter::error::InterpreterError;
 use crate::tmux::error::TmuxError;
 use common::error::CommonError;
+use snapshot::error::SnapshotError;
 use std::path::PathBuf;
 use std::{fmt, io};
 use yaml_rust;
@@ -13,7 +14,19 @@ pub enum LoadError {
     Common(CommonError),
     Tmux(TmuxError),
     Serialization(serde_saphyr::Error),
+    Snapshot(SnapshotError),
     Interpreter(InterpreterError),
+    Aborted(Option<String>, Vec<(String, TmuxError)>),
+    CommandsFailed(Vec<(String, TmuxError)>),
+    MalformedVar(String),
+    ExtendsCycle(Vec<PathBuf>),
+    ExtendsMissing(Vec<PathBuf>, io::Error),
+    NoSession(String),
+    NotInTmux,
+    NoWindows(PathBuf),
+    Parse(PathBuf, serde_saphyr::Error),
+    UnknownBackend(String),
+    UnresolvedVar(String, PathBuf),
 }
 
 impl fmt::Display for LoadError {
@@ -30,14 +43,86 @@ impl fmt::Display for LoadError {
                 e
             ),
             LoadError::Common(e) => write!(f, "{}", e),
+            LoadError::Aborted(killed, failed) => {
+                write!(f, "Loading stopped at a tmux command that failed")?;
+                if let Some(session) = killed {
+                    write!(f, ", the half built session `{}` was killed", session)?;
+                }
+                write!(f, ":{}", display_failures(failed))
+            }
+            LoadError::CommandsFailed(failed) => write!(
+                f,
+                "{} of the project's tmux commands failed:{}",
+                failed.len(),
+                display_failures(failed)
+            ),
             LoadError::Tmux(e) => write!(f, "{}", e),
             LoadError::Serialization(e) => write!(f, "{}", e),
+            LoadError::Snapshot(e) => write!(f, "Couldn't inspect the running session: {}", e),
+            LoadError::ExtendsCycle(chain) => {
+                write!(f, "The project extends itself: {}", display_chain(chain))
+            }
+            LoadError::ExtendsMissing(chain, e) => write!(
+                f,
+                "Couldn't read the project being extended: {}. Received error: {}",
+                display_chain(chain),
+                e
+            ),
+            LoadError::NoSession(name) => write!(
+                f,
+                "There's no running session named `{}` to load the project in to",
+                name
+            ),
+            LoadError::NotInTmux => write!(
+                f,
+                "`--into-current` needs muxed to be run from inside a tmux session"
+            ),
+            LoadError::NoWindows(file) => write!(
+                f,
+                "The project `{}` doesn't define any windows",
+                file.display()
+            ),
+            LoadError::Parse(file, e) => {
+                write!(f, "Couldn't parse the project `{}`: {}", file.display(), e)
+            }
+            LoadError::MalformedVar(arg) => write!(
+                f,
+                "Couldn't read the variable `{}`. Variables are passed as `key=value`",
+                arg
+            ),
+            LoadError::UnknownBackend(backend) => write!(
+                f,
+                "The backend `{}` isn't supported. Use `batch`, `control` or `each`",
+                backend
+            ),
+            LoadError::UnresolvedVar(name, file) => write!(
+                f,
+                "The variable `{}` used in `{}` has no value. Set it in `vars:`, the environment, or pass `{}=value`",
+                name,
+                file.display(),
+                name
+            ),
         }
     }
 }
 
 impl std::error::Error for LoadError {}
 
+fn display_failures(failed: &[(String, TmuxError)]) -> String {
+    failed
+        .iter()
+        .map(|(part, e)| format!("\n    {}: {}", part, e))
+        .collect()
+}
+
+fn display_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|file| format!("`{}`", file.display()))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
 impl From<CommonError> for LoadError {
     fn from(err: CommonError) -> LoadError {
         LoadError::Common(err)
@@ -68,6 +153,12 @@ impl From<serde_saphyr::Error> for LoadError {
     }
 }
 
+impl From<SnapshotError> for LoadError {
+    fn from(err: SnapshotError) -> LoadError {
+        LoadError::Snapshot(err)
+    }
+}
+
 impl From<InterpreterError> for LoadError {
     fn from(err: InterpreterError) -> LoadError {
         LoadError::Interpreter(err)
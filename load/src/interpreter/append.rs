@@ -0,0 +1,181 @@
+//! Appending a project to a session that's already running. Every window is
+//! created with `new-window` after the session's last window, instead of the
+//! first one creating the session.
+
+use crate::command::{Commands, Pre, SelectWindow};
+use crate::interpreter::error::InterpreterError;
+use crate::interpreter::reconcile::retarget;
+use crate::interpreter::to_command::{PlanContext, ToCommand};
+use crate::project;
+use common::tmux::{Session, Target};
+
+type Result<T> = std::result::Result<T, InterpreterError>;
+
+/// Plan the commands that add `project`'s windows to the running session it's
+/// named after, whose windows are at the indices in `taken`. The project's
+/// `pre` and `post` still run, and its active window, or else its first, is
+/// selected.
+///
+/// Unless it's daemonized the user is then attached or switched to the
+/// session, except when it's the `current` one they're already in.
+///
+/// `project` must already be enriched with the running session's name. Its
+/// windows are moved to the indices they're created at.
+pub fn append(project: &mut Session, taken: &[usize], current: bool) -> Result<Vec<Commands>> {
+    let session_name = project
+        .name
+        .clone()
+        .ok_or(InterpreterError::SessionNameRequired)?;
+    let config = project.config.as_ref();
+    let base_index = config.map(|c| c.base_index).unwrap_or(0);
+    let pane_base_index = config.map(|c| c.pane_base_index).unwrap_or(0);
+
+    let next = taken.iter().max().map_or(base_index, |max| max + 1);
+    project.windows = project
+        .windows
+        .iter()
+        .enumerate()
+        .map(|(offset, window)| retarget(window, &session_name, next + offset, pane_base_index))
+        .collect();
+    let project = &*project;
+
+    let mut commands: Vec<Commands> = vec![];
+
+    if let Some(pre) = project.pre() {
+        commands.extend(pre.iter().map(|cmd| Pre::new(cmd.clone()).into()));
+    }
+
+    let mut first: Option<Target> = None;
+    let mut active: Option<Target> = None;
+
+    for window in &project.windows {
+        let ctx = PlanContext {
+            first: false,
+            session: project,
+            window: Some(window),
+        };
+
+        commands.extend(window.to_commands(ctx)?);
+        for pane in &window.panes {
+            commands.extend(pane.to_commands(ctx)?);
+        }
+
+        if first.is_none() {
+            first = window.target.clone();
+        }
+        if window.active && active.is_none() {
+            active = window.target.clone();
+        }
+    }
+
+    if let Some(target) = active.or(first) {
+        commands.push(SelectWindow::new(target).into());
+    }
+
+    if let Some(post) = project.post.as_ref() {
+        commands.extend(post.iter().map(|cmd| Pre::new(cmd.clone()).into()));
+    }
+
+    if project.daemonize.is_none() && !current {
+        commands.push(project::open(&session_name));
+    }
+
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Config, Pane, Window};
+
+    fn session(windows: Vec<Window>) -> Session {
+        Session {
+            name: Some("work".into()),
+            target: Some(Target::new("work", None, None)),
+            config: Some(Config {
+                base_index: 1,
+                pane_base_index: 0,
+            }),
+            windows,
+            ..Default::default()
+        }
+    }
+
+    fn args(commands: &[Commands]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| command.as_trait().args().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn appends_every_window_after_the_last_running_one() {
+        let mut project = session(vec![
+            Window {
+                name: "psql".into(),
+                command: Some("psql".into()),
+                ..Default::default()
+            },
+            Window {
+                name: "redis".into(),
+                active: true,
+                panes: vec![Pane::default(), Pane::default()],
+                ..Default::default()
+            },
+        ]);
+
+        assert_eq!(
+            args(&append(&mut project, &[1, 2, 4], true).unwrap()),
+            vec![
+                "new-window -t work:5 -n psql",
+                "send-keys -t work:5 -l -- psql ; send-keys -t work:5 KPEnter",
+                "new-window -t work:6 -n redis",
+                "split-window -t work:6.0",
+                "select-window -t work:6",
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_at_the_base_index_of_an_empty_list() {
+        let mut project = session(vec![Window {
+            name: "psql".into(),
+            ..Default::default()
+        }]);
+        project.daemonize = Some(true);
+
+        assert_eq!(
+            args(&append(&mut project, &[], false).unwrap()),
+            vec!["new-window -t work:1 -n psql", "select-window -t work:1"]
+        );
+    }
+
+    #[test]
+    fn opens_a_session_that_isnt_the_current_one() {
+        let mut project = session(vec![Window {
+            name: "psql".into(),
+            ..Default::default()
+        }]);
+
+        let commands = append(&mut project, &[1], false).unwrap();
+        let last = commands.last().unwrap();
+
+        assert!(matches!(
+            last,
+            Commands::Attach(_) | Commands::SwitchClient(_)
+        ));
+        assert!(last.as_trait().args().ends_with(&["-t", "work"]));
+    }
+
+    #[test]
+    fn stays_in_the_current_session() {
+        let mut project = session(vec![Window {
+            name: "psql".into(),
+            ..Default::default()
+        }]);
+
+        let commands = append(&mut project, &[1], true).unwrap();
+
+        assert!(matches!(commands.last(), Some(Commands::SelectWindow(_))));
+    }
+}
@@ -1,10 +1,10 @@
 //! The YAML parser. Here is where we convert the yaml in to commands to be
 //! processed later.
 
-use common::project_paths::homedir;
+use common::project_paths::expand_path;
 use common::tmux::session::{NodeMut, Session};
-use common::tmux::{Config, Target};
-use std::path::{Component, Path, PathBuf};
+use common::tmux::tree::assign_targets;
+use common::tmux::{Config, Pane, Target};
 
 /// Enriches a `Session` with configuration details and contextual information,
 /// preparing command stacks for user tmux sessions.
@@ -22,7 +22,9 @@ use std::path::{Component, Path, PathBuf};
 ///
 /// Paths for windows and panes are expanded as needed, and both window and pane
 /// indices are offset according to the configuration values. Targets are assigned
-/// reflecting the enriched state for tmux session startup.
+/// reflecting the enriched state for tmux session startup. Nested panes default
+/// to the path of the pane holding them, and are targeted by where tmux numbers
+/// them once every split is made.
 pub fn enrich(session: &mut Session, project_name: String, daemonize: bool, config: Config) {
     let base_index = config.base_index;
     let pane_base_index = config.pane_base_index;
@@ -44,14 +46,8 @@ pub fn enrich(session: &mut Session, project_name: String, daemonize: bool, conf
     for node in session.iter_mut() {
         match node {
             NodeMut::Pane {
-                window_index,
-                window_path,
-                pane_index,
-                pane,
+                window_path, pane, ..
             } => {
-                let adjusted_window_index = window_index + base_index;
-                let adjusted_pane_index = pane_index + pane_base_index;
-
                 pane.path = match pane.path.as_ref() {
                     Some(path) => expand_path(path),
                     None => match window_path.as_ref() {
@@ -60,11 +56,7 @@ pub fn enrich(session: &mut Session, project_name: String, daemonize: bool, conf
                     },
                 };
 
-                pane.target = Some(Target::new(
-                    project_name.clone(),
-                    Some(adjusted_window_index),
-                    Some(adjusted_pane_index),
-                ));
+                nested_paths(pane);
             }
             NodeMut::Window { index, window } => {
                 let adjusted_index = index + base_index;
@@ -82,37 +74,29 @@ pub fn enrich(session: &mut Session, project_name: String, daemonize: bool, conf
             }
         }
     }
-}
 
-/// Expands a given path, replacing a leading `~` with the user's home directory if present.
-///
-/// If the path starts with `~`, this function attempts to resolve it to the current user's
-/// home directory and append any additional subpaths. If the path does not begin with `~`,
-/// it is returned unchanged. Returns `None` if home directory resolution fails.
-fn expand_path(path: &Path) -> Option<PathBuf> {
-    let mut components = path.components();
-
-    match components.next() {
-        Some(Component::Normal(os_str)) if os_str == "~" => {
-            if let Some(home) = homedir() {
-                let mut result = home;
-
-                for c in components {
-                    result.push(c.as_os_str());
-                }
-
-                Some(result)
-            } else {
-                None
-            }
+    for window in session.windows.iter_mut() {
+        if let Some(target) = window.target.as_ref() {
+            assign_targets(&mut window.panes, target, pane_base_index);
         }
-        _ => Some(path.to_path_buf()),
+    }
+}
+
+fn nested_paths(pane: &mut Pane) {
+    for nested in pane.panes.iter_mut() {
+        nested.path = match nested.path.as_ref() {
+            Some(path) => expand_path(path),
+            None => pane.path.clone(),
+        };
+
+        nested_paths(nested);
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use common::project_paths::homedir;
     use common::tmux::{Config, Target};
     use common::tmux::{Pane, Session, Window};
     use std::path::PathBuf;
@@ -361,46 +345,4 @@ mod test {
 
         assert_eq!(sess.windows[0].panes[0].path, Some(root_path));
     }
-
-    #[cfg(test)]
-    mod expand_path_tests {
-        use super::*;
-        use std::path::PathBuf;
-
-        fn mock_home() -> PathBuf {
-            // Use the actual homedir, but you can modify this for more control in the future
-            homedir().expect("Homedir should exist for test")
-        }
-
-        #[test]
-        fn expands_tilde_to_home() {
-            let input = PathBuf::from("~");
-            let result = expand_path(&input);
-            assert_eq!(result, Some(mock_home()));
-        }
-
-        #[test]
-        fn expands_tilde_prefix_with_subdirs() {
-            let input = PathBuf::from("~/some/folder");
-            let result = expand_path(&input);
-            let mut expected = mock_home();
-            expected.push("some");
-            expected.push("folder");
-            assert_eq!(result, Some(expected));
-        }
-
-        #[test]
-        fn leaves_absolute_path_unmodified() {
-            let input = PathBuf::from("/usr/local/bin");
-            let result = expand_path(&input);
-            assert_eq!(result, Some(PathBuf::from("/usr/local/bin")));
-        }
-
-        #[test]
-        fn leaves_relative_path_unmodified() {
-            let input = PathBuf::from("foo/bar/baz");
-            let result = expand_path(&input);
-            assert_eq!(result, Some(PathBuf::from("foo/bar/baz")));
-        }
-    }
 }
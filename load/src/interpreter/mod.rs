@@ -1,7 +1,11 @@
+mod append;
 mod enrichment;
 pub mod error;
 mod plan;
+mod reconcile;
 mod to_command;
 
+pub use append::append;
 pub use enrichment::enrich;
 pub use plan::plan;
+pub use reconcile::reconcile;
@@ -0,0 +1,221 @@
+//! Reconciling a running session with its project. Only the windows and panes
+//! missing from the running session are planned, everything already running is
+//! left untouched.
+
+use crate::command::{Commands, Layout, Split};
+use crate::interpreter::error::InterpreterError;
+use crate::interpreter::to_command::{PlanContext, ToCommand};
+use common::tmux::tree::assign_targets;
+use common::tmux::{Session, Target, Window, layout};
+
+type Result<T> = std::result::Result<T, InterpreterError>;
+
+/// Plan the commands that bring `running` in line with `project`. Windows are
+/// matched by name, in order. A window missing from the running session is
+/// created at its planned index, or after the last running window when that
+/// index is taken. A running window with fewer panes than planned has the
+/// missing panes split off its last pane. Nested panes are compared by the
+/// order tmux numbers them in, since the running window's panes are flat.
+///
+/// `project` must already be enriched so windows and panes have targets.
+pub fn reconcile(project: &Session, running: &Session) -> Result<Vec<Commands>> {
+    let session_name = project
+        .name
+        .as_ref()
+        .ok_or(InterpreterError::SessionNameRequired)?;
+    let pane_base_index = project
+        .config
+        .as_ref()
+        .map(|c| c.pane_base_index)
+        .unwrap_or(0);
+
+    let mut unmatched: Vec<&Window> = running.windows.iter().collect();
+    let mut taken: Vec<usize> = running
+        .windows
+        .iter()
+        .filter_map(|window| window.target.as_ref()?.window)
+        .collect();
+
+    let mut commands = vec![];
+
+    for window in &project.windows {
+        let ctx = |window| PlanContext {
+            first: false,
+            session: project,
+            window: Some(window),
+        };
+
+        match unmatched.iter().position(|w| w.name == window.name) {
+            Some(position) => {
+                let running = unmatched.remove(position);
+                let target = running
+                    .target
+                    .clone()
+                    .ok_or(InterpreterError::WindowTargetRequired)?;
+
+                let existing = running.panes.len().max(1);
+                let mut last = target.extend(existing - 1 + pane_base_index)?;
+                let planned = window.leaves();
+
+                for (index, pane) in planned.iter().enumerate().skip(existing) {
+                    let mut pane = (*pane).clone();
+                    pane.target = Some(target.extend(index + pane_base_index)?);
+
+                    commands.push(
+                        Split {
+                            direction: pane.split,
+                            size: pane.size.map(|size| size.to_string()),
+                            ..Split::new(last, pane.path.clone())
+                        }
+                        .into(),
+                    );
+                    commands.extend(pane.to_commands(ctx(window))?);
+                    last = pane
+                        .target
+                        .clone()
+                        .ok_or(InterpreterError::PaneTargetRequired)?;
+                }
+
+                if let (true, Some(layout)) = (planned.len() > existing, &window.layout) {
+                    commands.push(Layout::new(target, layout::fit(layout, planned.len())).into());
+                }
+            }
+            None => {
+                let planned = window
+                    .target
+                    .as_ref()
+                    .and_then(|target| target.window)
+                    .ok_or(InterpreterError::WindowTargetRequired)?;
+                let index = match taken.contains(&planned) {
+                    true => taken.iter().max().map_or(planned, |max| max + 1),
+                    false => planned,
+                };
+                taken.push(index);
+
+                let window = retarget(window, session_name, index, pane_base_index);
+                commands.extend(window.to_commands(ctx(&window))?);
+                for pane in &window.panes {
+                    commands.extend(pane.to_commands(ctx(&window))?);
+                }
+            }
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Point a window and its panes at a new window index.
+pub(crate) fn retarget(
+    window: &Window,
+    session_name: &str,
+    index: usize,
+    pane_base_index: usize,
+) -> Window {
+    let mut window = window.clone();
+    let target = Target::new(session_name, Some(index), None);
+
+    assign_targets(&mut window.panes, &target, pane_base_index);
+    window.target = Some(target);
+
+    window
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Config, Direction, Pane, Size};
+
+    fn window(name: &str, index: usize, panes: usize) -> Window {
+        let target = Target::new("muxed", Some(index), None);
+        Window {
+            name: name.into(),
+            panes: (0..panes)
+                .map(|pane| Pane {
+                    target: Some(target.extend(pane).unwrap()),
+                    ..Default::default()
+                })
+                .collect(),
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+
+    fn session(windows: Vec<Window>) -> Session {
+        Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            config: Some(Config {
+                base_index: 0,
+                pane_base_index: 0,
+            }),
+            windows,
+            ..Default::default()
+        }
+    }
+
+    fn args(commands: &[Commands]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| command.as_trait().args().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn plans_nothing_when_everything_is_running() {
+        let project = session(vec![window("editor", 0, 2), window("git", 1, 0)]);
+        let running = session(vec![window("editor", 0, 2), window("git", 1, 1)]);
+        assert!(reconcile(&project, &running).unwrap().is_empty());
+    }
+
+    #[test]
+    fn creates_a_missing_window_at_its_planned_index() {
+        let project = session(vec![window("editor", 0, 0), window("git", 1, 0)]);
+        let running = session(vec![window("editor", 0, 1)]);
+        let commands = reconcile(&project, &running).unwrap();
+        assert_eq!(args(&commands), vec!["new-window -t muxed:1 -n git"]);
+    }
+
+    #[test]
+    fn creates_a_missing_window_after_the_last_when_its_index_is_taken() {
+        let project = session(vec![window("editor", 0, 0), window("git", 1, 0)]);
+        let running = session(vec![window("editor", 0, 1), window("logs", 1, 1)]);
+        let commands = reconcile(&project, &running).unwrap();
+        assert_eq!(args(&commands), vec!["new-window -t muxed:2 -n git"]);
+    }
+
+    #[test]
+    fn splits_missing_panes_off_the_last_running_pane() {
+        let mut editor = window("editor", 0, 3);
+        editor.layout = Some("tiled".into());
+        let project = session(vec![editor]);
+        let running = session(vec![window("editor", 0, 1)]);
+        let commands = reconcile(&project, &running).unwrap();
+        assert_eq!(
+            args(&commands),
+            vec![
+                "split-window -t muxed:0.0",
+                "split-window -t muxed:0.1",
+                "select-layout -t muxed:0 tiled",
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_missing_panes_the_way_the_project_does() {
+        let mut editor = window("editor", 0, 2);
+        editor.panes[1].split = Some(Direction::Horizontal);
+        editor.panes[1].size = Some(Size::Percent(30));
+        let project = session(vec![editor]);
+        let running = session(vec![window("editor", 0, 1)]);
+        let commands = reconcile(&project, &running).unwrap();
+        assert_eq!(args(&commands), vec!["split-window -h -l 30% -t muxed:0.0"]);
+    }
+
+    #[test]
+    fn matches_windows_by_name_not_index() {
+        let project = session(vec![window("editor", 0, 0), window("git", 1, 2)]);
+        let running = session(vec![window("git", 3, 1), window("editor", 4, 1)]);
+        let commands = reconcile(&project, &running).unwrap();
+        assert_eq!(args(&commands), vec!["split-window -t muxed:3.0"]);
+    }
+}
@@ -1,15 +1,20 @@
 use crate::command::{
-    Attach, Commands, Layout, Pre, SelectPane, SelectWindow, SendKeys, Split, Window,
+    Attach, Commands, Keys, Layout, Pre, SelectPane, SelectWindow, SendKeys, Split, Window,
 };
 use crate::interpreter::error::InterpreterError;
-use common::tmux::Target;
+use crate::shell::quote;
 use common::tmux::session::{NodeRef, Session};
+use common::tmux::tree::{PaneTree, pane_at};
+use common::tmux::{Target, layout};
 
 type Result<T> = std::result::Result<T, InterpreterError>;
 
+#[derive(Clone, Copy)]
 pub(crate) struct PlanContext<'a> {
-    first: bool,
-    session: &'a Session,
+    pub(crate) first: bool,
+    pub(crate) session: &'a Session,
+    /// The window being planned, or the one the pane being planned is in.
+    pub(crate) window: Option<&'a common::tmux::Window>,
 }
 
 /// The `Plan` trait defines an interface for types that can generate
@@ -27,8 +32,22 @@ pub(crate) struct PlanContext<'a> {
 ///   - Iterate over windows and panes,
 ///     translating each into the commands required to create them in tmux
 ///   - Select the appropriate active window and pane
+///   - Insert any global post-commands
 ///   - If not in daemonized mode, add the command to attach to the session
 ///
+/// Each window and pane runs its setup commands in this order, before its own
+/// command:
+///   1. `cd` to its `path`
+///   2. `cat` its saved `scrollback`, in panes only
+///   3. The session's `pre_window`
+///   4. The session's `pre_pane`, in panes only
+///   5. The window's `pre`
+///   6. The pane's `pre`
+///
+/// A window with panes leaves `pre_window` and its `pre` to them, so they run
+/// once in every pane, the first one included. A pane's `keys` are sent after
+/// its command.
+///
 /// # Returns
 ///
 /// Returns a `Result<Vec<Commands>>`:
@@ -63,14 +82,18 @@ impl Plan for Session {
                     let ctx = PlanContext {
                         first,
                         session: self,
+                        window: Some(window),
                     };
                     commands.extend(window.to_commands(ctx)?);
                 }
                 // Add NodeRef::Pane logic as needed
-                NodeRef::Pane { pane, .. } => {
+                NodeRef::Pane {
+                    pane, window_index, ..
+                } => {
                     let ctx = PlanContext {
                         first: false,
                         session: self,
+                        window: self.windows.get(window_index),
                     };
                     commands.extend(pane.to_commands(ctx)?);
                 }
@@ -96,6 +119,10 @@ impl Plan for Session {
             commands.push(SelectPane::new(pane).into());
         }
 
+        if let Some(post) = self.post.as_ref() {
+            commands.extend(post.iter().map(|cmd| Pre::new(cmd.clone()).into()));
+        }
+
         if self.daemonize.is_none() {
             let target = match self.target.clone() {
                 Some(target) => target,
@@ -156,20 +183,50 @@ impl ToCommand for common::tmux::Window {
             commands.push(SendKeys::new(target.clone(), format!("cd {}", path.display())).into());
         }
 
-        if let Some(pre) = ctx.session.pre_window.as_ref() {
-            for cmd in pre.iter() {
-                commands.push(SendKeys::new(target.clone(), cmd.clone()).into());
+        // Panes run pre_window and the window's pre themselves
+        if self.panes.is_empty() {
+            for pre in ctx
+                .session
+                .pre_window()
+                .into_iter()
+                .chain(self.pre.as_ref())
+            {
+                for cmd in pre.iter() {
+                    commands.push(SendKeys::new(target.clone(), cmd.clone()).into());
+                }
             }
         }
 
-        // Create splits for additional panes (skip the first pane which uses the window)
+        // Create splits for additional panes (the first pane is the window's own)
         // Each split uses the individual pane's path, not the window's path
-        for pane in self.panes.iter().skip(1) {
-            commands.push(Split::new(target.clone(), pane.path.clone()).into());
+        let pane_base_index = ctx
+            .session
+            .config
+            .as_ref()
+            .map(|c| c.pane_base_index)
+            .unwrap_or(0);
+
+        for step in PaneTree::new(&self.panes).splits {
+            let Some(pane) = pane_at(&self.panes, &step.pane) else {
+                continue;
+            };
+
+            commands.push(
+                Split {
+                    direction: pane.split,
+                    size: pane.size.map(|size| size.to_string()),
+                    ..Split::new(
+                        target.extend(step.target + pane_base_index)?,
+                        pane.path.clone(),
+                    )
+                }
+                .into(),
+            );
         }
 
         if let Some(layout) = self.layout.as_ref() {
-            commands.push(Layout::new(target.clone(), layout.clone()).into());
+            let layout = layout::fit(layout, self.leaves().len());
+            commands.push(Layout::new(target.clone(), layout).into());
         }
 
         // Send the command
@@ -184,8 +241,11 @@ impl ToCommand for common::tmux::Window {
 /// Implementation of `ToCommand` for a tmux `Pane`.
 ///
 /// This method assembles a list of commands to realize a pane, typically
-/// navigating to the pane's path if specified, running pre-window commands,
-/// and sending the pane's custom command (if present).
+/// navigating to the pane's path if specified, replaying its saved scrollback,
+/// running pre-window and pre-pane commands, the window's and the pane's own
+/// pre commands, and sending the pane's custom command (if present) followed
+/// by its keys. A pane
+/// split in to panes is realized by its panes instead.
 impl ToCommand for common::tmux::Pane {
     fn to_commands(&self, ctx: PlanContext) -> Result<Vec<Commands>> {
         let mut commands: Vec<Commands> = vec![];
@@ -195,12 +255,39 @@ impl ToCommand for common::tmux::Pane {
             .clone()
             .ok_or(InterpreterError::PaneTargetRequired)?;
 
+        // A pane split in to panes is made up of them
+        if !self.panes.is_empty() {
+            for pane in &self.panes {
+                commands.extend(pane.to_commands(ctx)?);
+            }
+
+            if self.active {
+                commands.push(SelectPane::new(target).into());
+            }
+
+            return Ok(commands);
+        }
+
         // Navigate to pane-specific path if set
         if let Some(path) = self.path.as_ref() {
             commands.push(SendKeys::new(target.clone(), format!("cd {}", path.display())).into());
         }
 
-        if let Some(pre) = ctx.session.pre_window.as_ref() {
+        // Replay the pane's saved output before anything new is printed
+        if let Some(scrollback) = self.scrollback.as_ref() {
+            let file = scrollback.to_string_lossy();
+            commands.push(SendKeys::new(target.clone(), format!("cat {}", quote(&file))).into());
+        }
+
+        let window_pre = ctx.window.and_then(|window| window.pre.as_ref());
+        let pres = [
+            ctx.session.pre_window(),
+            ctx.session.pre_pane(),
+            window_pre,
+            self.pre.as_ref(),
+        ];
+
+        for pre in pres.into_iter().flatten() {
             for cmd in pre.iter() {
                 commands.push(SendKeys::new(target.clone(), cmd.clone()).into());
             }
@@ -210,6 +297,10 @@ impl ToCommand for common::tmux::Pane {
             commands.push(SendKeys::new(target.clone(), cmd.clone()).into());
         };
 
+        if !self.keys.is_empty() {
+            commands.push(Keys::new(target.clone(), self.keys.clone()).into());
+        }
+
         if self.active {
             commands.push(SelectPane::new(target).into());
         };
@@ -414,6 +505,51 @@ mod tests {
         assert_eq!(remains.len(), 1);
     }
 
+    #[test]
+    fn expect_custom_layouts_fitted_to_the_panes() {
+        let window = Window {
+            name: "editor".into(),
+            target: Some(Target::new("muxed", Some(0), None)),
+            panes: vec![
+                Pane {
+                    target: Some(Target::new("muxed", Some(0), Some(0))),
+                    ..Default::default()
+                },
+                Pane {
+                    target: Some(Target::new("muxed", Some(0), Some(1))),
+                    ..Default::default()
+                },
+            ],
+            layout: Some(
+                "4c1a,120x40,0,0{89x40,0,0,0,30x40,90,0[30x20,90,0,1,30x19,90,21,2]}".into(),
+            ),
+            ..Default::default()
+        };
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            windows: vec![window],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        let commands = session.command_plan().unwrap();
+        let layouts: Vec<_> = commands
+            .iter()
+            .filter_map(|x| match x {
+                Commands::Layout(layout) => Some(layout.layout.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            layouts,
+            vec![layout::with_checksum(
+                "120x40,0,0{89x40,0,0,0,30x40,90,0,1}"
+            )]
+        );
+    }
+
     #[test]
     fn expect_no_layout_for_window_without_layout() {
         let window = Window {
@@ -524,6 +660,7 @@ mod tests {
         let ctx = PlanContext {
             first: false,
             session: &session,
+            window: None,
         };
 
         let commands = pane.to_commands(ctx).unwrap();
@@ -564,6 +701,7 @@ mod tests {
         let ctx = PlanContext {
             first: false,
             session: &session,
+            window: None,
         };
 
         let commands = pane.to_commands(ctx).unwrap();
@@ -583,4 +721,308 @@ mod tests {
         assert_eq!(send_keys.len(), 1);
         assert_eq!(send_keys[0].exec, "vim");
     }
+
+    #[test]
+    fn expect_window_and_pane_pre_after_pre_window() {
+        use common::tmux::Pre as PreList;
+
+        let window = Window {
+            name: "frontend".into(),
+            target: Some(Target::new("muxed", Some(0), None)),
+            pre: Some(PreList(vec!["nvm use".into()])),
+            panes: vec![
+                Pane {
+                    command: Some("npm start".into()),
+                    target: Some(Target::new("muxed", Some(0), Some(0))),
+                    ..Default::default()
+                },
+                Pane {
+                    command: Some("npm test".into()),
+                    pre: Some(PreList(vec!["export CI=true".into()])),
+                    target: Some(Target::new("muxed", Some(0), Some(1))),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            pre_window: Some(PreList(vec!["source .env".into()])),
+            windows: vec![
+                window,
+                Window {
+                    name: "git".into(),
+                    target: Some(Target::new("muxed", Some(1), None)),
+                    ..Default::default()
+                },
+            ],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        let commands = session.command_plan().unwrap();
+        let sent: Vec<String> = commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                Commands::SendKeys(sk) => Some(format!("{} {}", sk.target.combined, sk.exec)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            sent,
+            vec![
+                "muxed:0.0 source .env",
+                "muxed:0.0 nvm use",
+                "muxed:0.0 npm start",
+                "muxed:0.1 source .env",
+                "muxed:0.1 nvm use",
+                "muxed:0.1 export CI=true",
+                "muxed:0.1 npm test",
+                "muxed:1 source .env",
+            ]
+        );
+    }
+
+    #[test]
+    fn expect_post_after_selection_and_before_attach() {
+        use common::tmux::Pre as PreList;
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            post: Some(PreList(vec!["notify-send muxed ready".into()])),
+            windows: vec![Window {
+                name: "cargo".into(),
+                target: Some(Target::new("muxed", Some(0), None)),
+                ..Default::default()
+            }],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        let commands = session.command_plan().unwrap();
+        let tail: Vec<&Commands> = commands.iter().rev().take(3).collect();
+
+        assert!(matches!(tail[0], Commands::Attach(_)));
+        assert!(matches!(tail[1], Commands::Pre(pre) if pre.exec == "notify-send muxed ready"));
+        assert!(matches!(tail[2], Commands::SelectPane(_)));
+    }
+
+    fn sent_keys(commands: &[Commands]) -> Vec<String> {
+        commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                Commands::SendKeys(sk) => Some(format!("{} {}", sk.target.combined, sk.exec)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn multi_pane_window(index: usize, panes: usize) -> Window {
+        let target = Target::new("muxed", Some(index), None);
+        Window {
+            name: format!("window{}", index),
+            panes: (0..panes)
+                .map(|pane| Pane {
+                    target: Some(target.extend(pane).unwrap()),
+                    ..Default::default()
+                })
+                .collect(),
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expect_scrollback_replayed_before_pre_commands() {
+        use common::tmux::Pre as PreList;
+
+        let mut window = multi_pane_window(0, 2);
+        window.panes[1].path = Some("/tmp".into());
+        window.panes[1].scrollback = Some("/home/me/.muxed/my project.scrollback/0.1".into());
+        window.panes[1].command = Some("tail -f log".into());
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            pre_window: Some(PreList(vec!["source .env".into()])),
+            windows: vec![window],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        let sent = sent_keys(&session.command_plan().unwrap());
+        assert_eq!(
+            sent,
+            vec![
+                "muxed:0.0 source .env",
+                "muxed:0.1 cd /tmp",
+                "muxed:0.1 cat '/home/me/.muxed/my project.scrollback/0.1'",
+                "muxed:0.1 source .env",
+                "muxed:0.1 tail -f log",
+            ]
+        );
+    }
+
+    #[test]
+    fn expect_keys_sent_after_the_command() {
+        let mut window = multi_pane_window(0, 2);
+        window.panes[1].command = Some("vim".into());
+        window.panes[1].keys = vec!["Escape".into(), ":Explore".into(), "Enter".into()];
+        window.panes[1].active = true;
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            windows: vec![window],
+            config: Some(basic_config()),
+            daemonize: Some(true),
+            ..Default::default()
+        };
+
+        let commands = session.command_plan().unwrap();
+        let position = |wanted: &dyn Fn(&Commands) -> bool| commands.iter().position(wanted);
+
+        let command = position(&|cmd| matches!(cmd, Commands::SendKeys(sk) if sk.exec == "vim"));
+        let keys = position(&|cmd| {
+            matches!(cmd, Commands::Keys(k) if k.target.combined == "muxed:0.1"
+                && k.keys == ["Escape", ":Explore", "Enter"])
+        });
+        let select = position(&|cmd| matches!(cmd, Commands::SelectPane(_)));
+
+        assert!(command < keys && keys < select, "{:?}", commands);
+    }
+
+    #[test]
+    fn expect_pre_window_once_in_every_pane_of_a_multi_pane_window() {
+        use common::tmux::Pre as PreList;
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            pre: Some(PreList(vec!["docker compose up -d".into()])),
+            pre_window: Some(PreList(vec!["source .env".into()])),
+            windows: vec![multi_pane_window(0, 3), multi_pane_window(1, 0)],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sent_keys(&session.command_plan().unwrap()),
+            vec![
+                "muxed:0.0 source .env",
+                "muxed:0.1 source .env",
+                "muxed:0.2 source .env",
+                "muxed:1 source .env",
+            ]
+        );
+    }
+
+    #[test]
+    fn expect_pre_pane_only_in_split_panes() {
+        use common::tmux::Pre as PreList;
+
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            pre_window: Some(PreList(vec!["source .env".into()])),
+            pre_pane: Some(PreList(vec!["clear".into()])),
+            windows: vec![multi_pane_window(0, 0), multi_pane_window(1, 2)],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sent_keys(&session.command_plan().unwrap()),
+            vec![
+                "muxed:0 source .env",
+                "muxed:1.0 source .env",
+                "muxed:1.0 clear",
+                "muxed:1.1 source .env",
+                "muxed:1.1 clear",
+            ]
+        );
+    }
+
+    #[test]
+    fn expect_one_split_per_extra_pane() {
+        let session = Session {
+            name: Some("muxed".into()),
+            target: Some(Target::new("muxed", None, None)),
+            windows: vec![multi_pane_window(0, 4)],
+            config: Some(basic_config()),
+            ..Default::default()
+        };
+
+        let commands = session.command_plan().unwrap();
+        let splits = commands
+            .iter()
+            .filter(|x| matches!(x, Commands::Split(_)))
+            .count();
+        assert_eq!(splits, 3);
+    }
+
+    #[test]
+    fn expect_nested_splits_to_target_panes_by_position() {
+        use crate::interpreter::enrich;
+        use common::tmux::{Direction, Size};
+
+        // The editor on the left, two terminals stacked on the right.
+        let mut session = Session {
+            windows: vec![Window {
+                name: "editor".into(),
+                panes: vec![
+                    Pane {
+                        command: Some("vim".into()),
+                        ..Default::default()
+                    },
+                    Pane {
+                        split: Some(Direction::Horizontal),
+                        size: Some(Size::Percent(40)),
+                        panes: vec![
+                            Pane {
+                                command: Some("htop".into()),
+                                ..Default::default()
+                            },
+                            Pane {
+                                command: Some("bash".into()),
+                                split: Some(Direction::Vertical),
+                                ..Default::default()
+                            },
+                        ],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let config = Config {
+            base_index: 0,
+            pane_base_index: 1,
+        };
+        enrich(&mut session, "muxed".into(), true, config);
+
+        let commands = session.command_plan().unwrap();
+        let splits: Vec<String> = commands
+            .iter()
+            .filter(|cmd| matches!(cmd, Commands::Split(_)))
+            .map(|cmd| cmd.as_trait().args().join(" "))
+            .collect();
+
+        assert_eq!(
+            splits,
+            vec![
+                "split-window -h -l 40% -t muxed:0.1",
+                "split-window -v -t muxed:0.2",
+            ]
+        );
+        assert_eq!(
+            sent_keys(&commands),
+            vec!["muxed:0.1 vim", "muxed:0.2 htop", "muxed:0.3 bash"]
+        );
+    }
 }
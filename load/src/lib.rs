@@ -1,18 +1,22 @@
 extern crate dirs;
-extern crate libc;
 extern crate yaml_rust;
 
 extern crate common;
 
+pub mod backend;
 pub mod command;
 pub mod error;
 mod interpreter;
 pub mod project;
+pub mod shell;
 pub mod tmux;
+pub mod vars;
 
 use crate::error::LoadError;
 use args::Args;
-use command::Commands;
+use backend::{Backend, Policy};
+use command::{Command, Commands, KillSession};
+use project::Project;
 
 use common::project_paths::ProjectPaths;
 use common::tmux::Config;
@@ -21,9 +25,18 @@ use common::{DEBUG, args, first_run};
 type Result<T> = std::result::Result<T, LoadError>;
 
 pub fn load(args: Args) -> Result<()> {
-    let project_paths = ProjectPaths::try_from(&args)?;
+    if args.flag_into_current || args.flag_into.is_some() {
+        return load_into(args);
+    }
+
+    if args.flag_dry_run {
+        return dry_run(args);
+    }
+
+    let project_paths = ProjectPaths::resolve(&args)?;
 
     let mut project = project::read(&args.arg_project, project_paths)?;
+    project.interpolate(&args.vars())?;
     let name = project.name().to_string();
 
     if DEBUG.load() {
@@ -32,6 +45,15 @@ pub fn load(args: Args) -> Result<()> {
     }
 
     let commands: Vec<Commands> = match project::session_exists(project.name()) {
+        Some(c) if args.flag_reconcile => {
+            let config = Config::from_string(tmux::get_config()?);
+            interpreter::enrich(project.session_mut(), name, args.flag_d, config);
+            let mut commands = reconcile(&project)?;
+            if !args.flag_d {
+                commands.push(c);
+            }
+            commands
+        }
         Some(c) => {
             vec![c]
         }
@@ -50,9 +72,140 @@ pub fn load(args: Args) -> Result<()> {
         dbg!(&commands);
     };
 
-    for command in &commands {
-        command.as_trait().call()?;
+    execute(&args, &project, &commands)
+}
+
+/// Add the project's windows to a running session, `--into <session>` or the
+/// one muxed is run from with `--into-current`, after the windows it already
+/// has. The project's own session name is ignored.
+fn load_into(args: Args) -> Result<()> {
+    let target = match args.flag_into.clone() {
+        Some(name) => name,
+        None => tmux::current_session().ok_or(LoadError::NotInTmux)?,
+    };
+
+    if !tmux::has_session(&target) {
+        return Err(LoadError::NoSession(target));
+    }
+
+    let project_paths = ProjectPaths::resolve(&args)?;
+
+    let mut project = project::read(&args.arg_project, project_paths)?;
+    project.interpolate(&args.vars())?;
+
+    let config = Config::from_string(tmux::get_config()?);
+    interpreter::enrich(project.session_mut(), target.clone(), args.flag_d, config);
+
+    let taken = tmux::window_indices(&target)?;
+    let current = args.flag_into.is_none();
+    let commands = interpreter::append(project.session_mut(), &taken, current)?;
+
+    if args.flag_dry_run {
+        for command in &commands {
+            println!("{}", shell::render(command));
+        }
+        return Ok(());
+    }
+
+    execute(&args, &project, &commands)
+}
+
+/// Run the planned commands with the backend and policy from the args. When a
+/// command fails and the plan was creating the session, aborting kills what
+/// was built of it. Every failure is reported with the window or pane it was
+/// for.
+pub fn execute(args: &Args, project: &Project, commands: &[Commands]) -> Result<()> {
+    let backend = Backend::from_arg(args.flag_backend.as_deref())?;
+    let policy = match args.flag_keep_going {
+        true => Policy::KeepGoing,
+        false => Policy::Abort,
+    };
+
+    let Err(failures) = backend::run(commands, backend, project.name(), policy) else {
+        return Ok(());
+    };
+
+    let last = &commands[failures[failures.len() - 1].index];
+    let failed = failures
+        .into_iter()
+        .map(|failure| (locate(project, &commands[failure.index]), failure.error))
+        .collect();
+
+    if policy == Policy::KeepGoing {
+        return Err(LoadError::CommandsFailed(failed));
+    }
+
+    // Once it's attaching the session is built, and a plan that doesn't create
+    // the session is adding to one that's the user's.
+    let building = !matches!(last, Commands::Attach(_) | Commands::SwitchClient(_));
+    let creating = commands.iter().any(|c| matches!(c, Commands::Session(_)));
+    let killed = (building && creating).then(|| {
+        let _ = KillSession::new(project.name()).call();
+        project.name().to_string()
+    });
+
+    Err(LoadError::Aborted(killed, failed))
+}
+
+/// Describe the part of the project a planned command was for.
+fn locate(project: &Project, command: &Commands) -> String {
+    let Some(target) = command.target() else {
+        return "pre or post".to_string();
+    };
+
+    let window = target.window.and_then(|index| {
+        project
+            .session()
+            .windows
+            .iter()
+            .find(|window| window.target.as_ref().and_then(|t| t.window) == Some(index))
+    });
+
+    match (window, target.window) {
+        (Some(window), _) => format!("window `{}` ({})", window.name, target.combined),
+        (None, Some(_)) => format!("`{}`", target.combined),
+        (None, None) => format!("session `{}`", target.combined),
+    }
+}
+
+/// Print the commands `load` would run for a project as copy-pasteable shell
+/// lines without running any of them. The full plan is printed even when the
+/// session is already running, unless `--reconcile` is given, then only what's
+/// missing from it is.
+fn dry_run(args: Args) -> Result<()> {
+    let (project, commands) = plan(&args, args.flag_d)?;
+    let commands = match args.flag_reconcile && tmux::has_session(project.name()) {
+        true => reconcile(&project)?,
+        false => commands,
+    };
+
+    for command in commands {
+        println!("{}", shell::render(&command));
     }
 
     Ok(())
 }
+
+/// Inspect the project's running session and plan only the windows and panes
+/// missing from it. The project must already be enriched.
+fn reconcile(project: &Project) -> Result<Vec<Commands>> {
+    let running = snapshot::inspect(project.name())?;
+    Ok(interpreter::reconcile(project.session(), &running)?)
+}
+
+/// Read, enrich and plan a project without executing anything. The config is
+/// read from a running tmux server when there is one, otherwise tmux's
+/// defaults are used, so a plan can be built on any machine.
+pub fn plan(args: &Args, daemonize: bool) -> Result<(Project, Vec<Commands>)> {
+    let project_paths = ProjectPaths::resolve(args)?;
+
+    let mut project = project::read(&args.arg_project, project_paths)?;
+    project.interpolate(&args.vars())?;
+    let name = project.name().to_string();
+
+    let config = Config::from_string(tmux::get_running_config().unwrap_or_default());
+    interpreter::enrich(project.session_mut(), name, daemonize, config);
+    let commands = interpreter::plan(&project)?;
+
+    Ok((project, commands))
+}
@@ -6,14 +6,15 @@ use crate::command::{Attach, Commands, SwitchClient};
 use crate::error::LoadError;
 use crate::first_run::check_first_run;
 use crate::tmux::has_session;
-use common::project_paths::ProjectPaths;
+use crate::vars::{Vars, interpolate};
+use common::project_paths::{CONFIG_EXTENSION, ProjectPaths};
 use common::tmux::Target;
 use common::tmux::session::Session;
 use std::env;
-use std::fs::File;
-use std::io::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-static TMUX_ENV_VAR: &str = "TMUX";
+pub(crate) static TMUX_ENV_VAR: &str = "TMUX";
 
 pub struct Project {
     name: String,
@@ -37,12 +38,28 @@ impl Project {
     pub fn session_mut(&mut self) -> &mut Session {
         &mut self.session
     }
+
+    /// Resolve the `${var}` and `{{var}}` placeholders in the project using
+    /// the `key=value` pairs from the command line, the project's `vars:`
+    /// map, and the environment.
+    pub fn interpolate(&mut self, args: &[String]) -> Result<(), LoadError> {
+        let vars = Vars::new(args, self.session.vars.take())
+            .map_err(|arg| LoadError::MalformedVar(arg.to_string()))?;
+
+        interpolate(&mut self.session, &vars)
+            .map_err(|name| LoadError::UnresolvedVar(name, self.paths.project_file.clone()))
+    }
 }
 
 /// Using the provided project name, locate the path to that project file. It
 /// should be something similar to: `~/.muxed/my_project.yml`
 /// Read in the contents of the config (which should be Yaml), and parse the
-/// contents as yaml.
+/// contents as yaml. If the project `extends:` another project, that project is
+/// read from the same directory and merged in, following the chain until a
+/// project doesn't extend anything.
+///
+/// Local projects, discovered from the current directory, are named after the
+/// directory they live in and use it as their `root` unless they set one.
 ///
 /// `project_name`: The name of the project, corresponding to the project config
 /// file.
@@ -80,20 +97,30 @@ impl Project {
 pub fn read(project_name: &str, project_paths: ProjectPaths) -> Result<Project, LoadError> {
     check_first_run(&project_paths.project_directory)?;
 
-    let mut file = File::open(&project_paths.project_file).map_err(|e| {
-        LoadError::Read(
+    let mut session = parse(&project_paths.project_file).map_err(|e| match e {
+        LoadError::Io(e) => LoadError::Read(
             project_name.to_string(),
             project_paths.project_directory.clone(),
             e,
-        )
+        ),
+        e => e,
     })?;
-    let mut contents = String::new();
 
-    file.read_to_string(&mut contents)?;
-    let session = serde_saphyr::from_str(&contents)?;
+    if session.windows.is_empty() {
+        return Err(LoadError::NoWindows(project_paths.project_file));
+    }
+
+    if project_paths.local && session.root.is_none() {
+        session.root = Some(project_paths.project_directory.clone());
+    }
+
+    let name = project_paths
+        .local_name()
+        .unwrap_or(project_name)
+        .to_string();
 
     let project = Project {
-        name: project_name.to_string(),
+        name,
         paths: project_paths,
         session,
     };
@@ -101,6 +128,50 @@ pub fn read(project_name: &str, project_paths: ProjectPaths) -> Result<Project,
     Ok(project)
 }
 
+/// Parse a project file and merge in every project it `extends:`. Parent
+/// projects are looked up in the same directory as the file.
+pub fn parse(project_file: &Path) -> Result<Session, LoadError> {
+    let contents = fs::read_to_string(project_file)?;
+    let session = parse_str(&contents, project_file)?;
+
+    let directory = project_file.parent().unwrap_or(Path::new(""));
+    let mut chain = vec![project_file.to_path_buf()];
+    inherit(session, directory, &mut chain)
+}
+
+fn parse_str(contents: &str, file: &Path) -> Result<Session, LoadError> {
+    serde_saphyr::from_str(contents).map_err(|e| LoadError::Parse(file.to_path_buf(), e))
+}
+
+/// Follow the `extends:` chain of a session, merging each parent in to the
+/// session that extends it. `chain` holds every file read so far so cycles can
+/// be caught and reported.
+fn inherit(
+    mut session: Session,
+    directory: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Session, LoadError> {
+    let Some(parent_name) = session.extends.take() else {
+        return Ok(session);
+    };
+
+    let parent_file = directory.join(parent_name).with_extension(CONFIG_EXTENSION);
+    let cycle = chain.contains(&parent_file);
+    chain.push(parent_file.clone());
+
+    if cycle {
+        return Err(LoadError::ExtendsCycle(chain.clone()));
+    }
+
+    let contents = fs::read_to_string(&parent_file)
+        .map_err(|e| LoadError::ExtendsMissing(chain.clone(), e))?;
+    let parent = parse_str(&contents, &parent_file)?;
+    let parent = inherit(parent, directory, chain)?;
+
+    session.inherit(parent);
+    Ok(session)
+}
+
 /// Find out if a tmux session is already active with this name. If it is active
 /// return `Some<Commands::Attach>` with a command to attach to the session. If a
 /// session is not active return None and let the app carry on.
@@ -143,7 +214,8 @@ mod test {
     use super::*;
     use common::rand_names;
     use std::env::temp_dir;
-    use std::fs;
+    use std::fs::{self, File};
+    use std::io::prelude::*;
 
     #[test]
     fn missing_file_returns_err() {
@@ -166,7 +238,7 @@ mod test {
 
         let result = read(&name, project_paths);
         let _ = fs::remove_file(remove_file);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(LoadError::Parse(..))));
     }
 
     #[test]
@@ -190,25 +262,110 @@ mod test {
         assert!(result.is_ok());
     }
 
+    fn write_project(directory: &Path, name: &str, contents: &str) {
+        let _ = fs::create_dir(directory);
+        let file = directory.join(name).with_extension(CONFIG_EXTENSION);
+        fs::write(file, contents).unwrap();
+    }
+
+    #[test]
+    fn extends_merges_the_parent_project() {
+        let base = rand_names::project_file_name();
+        let name = rand_names::project_file_name();
+        let project_paths =
+            ProjectPaths::from_strs(temp_dir().to_str().unwrap(), ".muxed", &name, "");
+        let directory = project_paths.project_directory.clone();
+
+        write_project(&directory, &base, "windows: ['editor', 'logs']");
+        write_project(
+            &directory,
+            &name,
+            &format!("extends: {}\nwindows: ['tests']", base),
+        );
+
+        let result = read(&name, project_paths);
+        let _ = fs::remove_file(directory.join(&base).with_extension(CONFIG_EXTENSION));
+        let _ = fs::remove_file(directory.join(&name).with_extension(CONFIG_EXTENSION));
+
+        let names: Vec<String> = result
+            .unwrap()
+            .session()
+            .windows
+            .iter()
+            .map(|w| w.name.clone())
+            .collect();
+        assert_eq!(names, ["editor", "logs", "tests"]);
+    }
+
+    #[test]
+    fn extends_reports_cycles_with_the_chain() {
+        let first = rand_names::project_file_name();
+        let second = rand_names::project_file_name();
+        let project_paths =
+            ProjectPaths::from_strs(temp_dir().to_str().unwrap(), ".muxed", &first, "");
+        let directory = project_paths.project_directory.clone();
+
+        write_project(&directory, &first, &format!("extends: {}", second));
+        write_project(&directory, &second, &format!("extends: {}", first));
+
+        let result = read(&first, project_paths);
+        let _ = fs::remove_file(directory.join(&first).with_extension(CONFIG_EXTENSION));
+        let _ = fs::remove_file(directory.join(&second).with_extension(CONFIG_EXTENSION));
+
+        match result {
+            Err(LoadError::ExtendsCycle(chain)) => assert_eq!(chain.len(), 3),
+            _ => panic!("Expected LoadError::ExtendsCycle"),
+        }
+    }
+
+    #[test]
+    fn extends_reports_missing_parents() {
+        let name = rand_names::project_file_name();
+        let project_paths =
+            ProjectPaths::from_strs(temp_dir().to_str().unwrap(), ".muxed", &name, "");
+        let directory = project_paths.project_directory.clone();
+
+        write_project(&directory, &name, "extends: muxed-not-a-project");
+
+        let result = read(&name, project_paths);
+        let _ = fs::remove_file(directory.join(&name).with_extension(CONFIG_EXTENSION));
+
+        match result {
+            Err(LoadError::ExtendsMissing(chain, _)) => assert_eq!(chain.len(), 2),
+            _ => panic!("Expected LoadError::ExtendsMissing"),
+        }
+    }
+
+    #[test]
+    fn local_project_defaults_root_and_name_to_its_directory() {
+        let directory = rand_names::project_path();
+        let _ = fs::create_dir(&directory);
+        let project_file = directory.join(".muxed.yml");
+        fs::write(&project_file, "windows: ['editor']").unwrap();
+
+        let project_paths = ProjectPaths::local(temp_dir(), project_file);
+        let result = read("", project_paths);
+        let _ = fs::remove_dir_all(&directory);
+
+        let project = result.unwrap();
+        let directory_name = directory.file_name().unwrap().to_str().unwrap();
+        assert_eq!(project.name(), directory_name);
+        assert_eq!(project.session().root, Some(directory));
+    }
+
     #[test]
     fn open_returns_attach_in_bare_context() {
-        let _ = unsafe { env::remove_var(TMUX_ENV_VAR) };
-        let attach_command = match open("muxed") {
-            Commands::Attach(_) => true,
-            _ => false,
-        };
+        unsafe { env::remove_var(TMUX_ENV_VAR) };
+        let attach_command = matches!(open("muxed"), Commands::Attach(_));
 
         assert!(attach_command);
     }
 
     #[test]
     fn open_returns_switch_client_in_nested_context() {
-        let _ = unsafe { env::set_var(TMUX_ENV_VAR, "somestring") };
-        let switch_command = match open("muxed") {
-            Commands::SwitchClient(_) => true,
-            _ => false,
-        };
-        let _ = unsafe { env::remove_var(TMUX_ENV_VAR) };
+        unsafe { env::set_var(TMUX_ENV_VAR, "somestring") };
+        let switch_command = matches!(open("muxed"), Commands::SwitchClient(_));
+        unsafe { env::remove_var(TMUX_ENV_VAR) };
 
         assert!(switch_command);
     }
@@ -0,0 +1,111 @@
+//! Renders planned commands as POSIX shell lines. Used to show what muxed would
+//! run without running it.
+
+use crate::command::Commands;
+use crate::tmux::TMUX_NAME;
+use std::borrow::Cow;
+
+/// Characters that never need quoting in a POSIX shell word.
+static SAFE_CHARS: &str = "-_./:=@%+,";
+
+/// Quote a single argument so a POSIX shell passes it through untouched.
+/// Arguments made only of safe characters are returned as is, everything else
+/// is wrapped in single quotes with any inner single quotes escaped.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate load;
+/// use load::shell::quote;
+///
+/// assert_eq!(quote("muxed:1.0"), "muxed:1.0");
+/// assert_eq!(quote("it's here"), r"'it'\''s here'");
+/// ```
+pub fn quote(arg: &str) -> Cow<'_, str> {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || SAFE_CHARS.contains(c));
+
+    if safe {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+/// Join a program and its arguments into one quoted shell line.
+pub fn line(program: &str, args: &[&str]) -> String {
+    let mut parts = vec![quote(program)];
+    parts.extend(args.iter().map(|arg| quote(arg)));
+    parts.join(" ")
+}
+
+/// Render a planned command as the shell line that reproduces it. `Pre`
+/// commands run on the host, split on spaces the same way `Pre::call` does,
+/// everything else is a `tmux` invocation built from `Command::args()`.
+pub fn render(command: &Commands) -> String {
+    match command {
+        Commands::Pre(pre) => {
+            let parts: Vec<&str> = pre.exec.split(' ').collect();
+            match parts.split_first() {
+                Some((program, args)) => line(program, args),
+                None => String::new(),
+            }
+        }
+        _ => line(TMUX_NAME, &command.as_trait().args()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::{Attach, Pre, SendKeys};
+    use common::tmux::Target;
+
+    #[test]
+    fn leaves_safe_args_unquoted() {
+        assert_eq!(quote("new-window"), "new-window");
+        assert_eq!(quote("/tmp/project"), "/tmp/project");
+    }
+
+    #[test]
+    fn quotes_empty_args() {
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn quotes_spaces_and_metacharacters() {
+        assert_eq!(quote("name with spaces"), "'name with spaces'");
+        assert_eq!(quote("echo $HOME; ls"), "'echo $HOME; ls'");
+        assert_eq!(quote("~/projects"), "'~/projects'");
+    }
+
+    #[test]
+    fn escapes_single_quotes() {
+        assert_eq!(quote("echo 'hi'"), r"'echo '\''hi'\'''");
+    }
+
+    #[test]
+    fn renders_send_keys_as_tmux_line() {
+        let target = Target::new("muxed", Some(0), None);
+        let command: Commands = SendKeys::new(target, "vim .".into()).into();
+        assert_eq!(
+            render(&command),
+            "tmux send-keys -t muxed:0 -l -- 'vim .' ';' send-keys -t muxed:0 KPEnter"
+        );
+    }
+
+    #[test]
+    fn renders_attach_without_redirect() {
+        let target = Target::new("my session", None, None);
+        let command: Commands = Attach::new(target, None).into();
+        assert_eq!(render(&command), "tmux attach -t 'my session'");
+    }
+
+    #[test]
+    fn renders_pre_as_host_command() {
+        let command: Commands = Pre::new("touch /tmp/muxed file".into()).into();
+        assert_eq!(render(&command), "touch /tmp/muxed file");
+    }
+}
@@ -0,0 +1,238 @@
+//! A control mode (`tmux -C`) connection. Commands are written to the client
+//! one line at a time and tmux answers each of them with a block of output
+//! between `%begin` and `%end`, or `%error` when the command failed, so every
+//! command's success and tmux's own error text are known. Lines outside of a
+//! block are notifications and are skipped.
+//!
+//! A control client has to be attached to a session to stay connected, so the
+//! connection is made to a session that's already running.
+
+use crate::shell::quote;
+use crate::tmux::TMUX_NAME;
+use crate::tmux::error::TmuxError;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+type Result<T> = std::result::Result<T, TmuxError>;
+
+/// An open control mode client.
+pub struct Control {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Control {
+    /// Attach a control client to `session`. The client doesn't receive pane
+    /// output and its size is ignored, so it leaves the session as it is.
+    pub fn attach(session: &str) -> Result<Control> {
+        let mut child = Command::new(TMUX_NAME)
+            .args(["-C", "attach", "-t", session, "-f", "no-output,ignore-size"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().ok_or_else(closed)?;
+        let mut stdout = BufReader::new(child.stdout.take().ok_or_else(closed)?);
+
+        // The first block answers the attach itself
+        if let Reply::Failed(lines) = read_reply(&mut stdout)? {
+            let _ = child.wait();
+            return Err(failed(&["-C", "attach", "-t", session], lines));
+        }
+
+        Ok(Control {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Run a tmux command, given as the args it would be called with on the
+    /// command line, and return its output. An arg of `;` separates commands
+    /// as it does there, each of them is answered in turn.
+    pub fn run(&mut self, args: &[&str]) -> Result<Vec<String>> {
+        let (line, commands) = to_line(args);
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()?;
+
+        let mut output = vec![];
+        for _ in 0..commands {
+            match read_reply(&mut self.stdout)? {
+                Reply::Done(lines) => output.extend(lines),
+                Reply::Failed(lines) => return Err(failed(args, lines)),
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Disconnect the client and wait for it to exit.
+    pub fn close(self) -> Result<()> {
+        let Control {
+            mut child, stdin, ..
+        } = self;
+        drop(stdin);
+        child.wait()?;
+        Ok(())
+    }
+}
+
+/// A command tmux answered with an error. There's no exit status in control
+/// mode, only the error's text.
+fn failed(args: &[&str], lines: Vec<String>) -> TmuxError {
+    TmuxError::CommandFailed {
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        status: None,
+        stderr: lines.join("\n"),
+    }
+}
+
+fn closed() -> TmuxError {
+    TmuxError::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the tmux control client closed",
+    ))
+}
+
+/// Turn command line args in to a line tmux reads the same way, and count the
+/// commands in it. On the command line tmux ends a command at an arg ending
+/// in `;`, unless it's escaped as `\;`. In a line the separator is a word of
+/// its own and every other word is quoted.
+fn to_line(args: &[&str]) -> (String, usize) {
+    let mut words: Vec<String> = vec![];
+    let mut commands = 1;
+
+    for arg in args {
+        match arg.strip_suffix(';') {
+            Some(rest) if rest.ends_with('\\') => {
+                words.push(quote(&format!("{};", &rest[..rest.len() - 1])).into_owned());
+            }
+            Some(rest) => {
+                if !rest.is_empty() {
+                    words.push(quote(rest).into_owned());
+                }
+                words.push(";".to_string());
+                commands += 1;
+            }
+            None => words.push(quote(arg).into_owned()),
+        }
+    }
+
+    (words.join(" "), commands)
+}
+
+/// tmux's answer to a command.
+#[derive(Debug, PartialEq)]
+enum Reply {
+    Done(Vec<String>),
+    Failed(Vec<String>),
+}
+
+/// Read up to the end of the next block. The lines that close a block repeat
+/// the time, number and flags its `%begin` line was given.
+fn read_reply(reader: &mut impl BufRead) -> Result<Reply> {
+    let mut block: Option<(String, Vec<String>)> = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(closed());
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        let Some((id, lines)) = block.as_mut() else {
+            if let Some(guard) = line.strip_prefix("%begin ") {
+                block = Some((guard.to_string(), vec![]));
+            } else if line == "%exit" || line.starts_with("%exit ") {
+                return Err(closed());
+            }
+            continue;
+        };
+
+        let ended = |tag: &str| {
+            line.strip_prefix(tag)
+                .is_some_and(|guard| guard == id.as_str())
+        };
+
+        if ended("%end ") {
+            return Ok(Reply::Done(std::mem::take(lines)));
+        } else if ended("%error ") {
+            return Ok(Reply::Failed(std::mem::take(lines)));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn separates_commands_with_a_bare_semicolon() {
+        let args = [
+            "send-keys",
+            "-t",
+            "muxed:1",
+            "-l",
+            "--",
+            "it's",
+            ";",
+            "send-keys",
+            "-t",
+            "muxed:1",
+            "KPEnter",
+        ];
+        assert_eq!(
+            to_line(&args),
+            (
+                r"send-keys -t muxed:1 -l -- 'it'\''s' ; send-keys -t muxed:1 KPEnter".to_string(),
+                2
+            )
+        );
+    }
+
+    #[test]
+    fn reads_escaped_semicolons_as_text() {
+        assert_eq!(
+            to_line(&["display-message", "-p", r"make\;"]),
+            ("display-message -p 'make;'".to_string(), 1)
+        );
+        assert_eq!(
+            to_line(&["display-message", "-p", r"rm {} \\;"]),
+            (r"display-message -p 'rm {} \;'".to_string(), 1)
+        );
+        assert_eq!(
+            to_line(&["show-options", "-g;", "show-options", "-g", "-w"]),
+            ("show-options -g ; show-options -g -w".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn skips_notifications_between_blocks() {
+        let mut reader = "%session-changed $1 muxed\n%window-add @2\n\
+                          %begin 1 11 1\n%1 0\n%end 1 11 1\n"
+            .as_bytes();
+        assert_eq!(
+            read_reply(&mut reader).unwrap(),
+            Reply::Done(vec!["%1 0".into()])
+        );
+    }
+
+    #[test]
+    fn returns_tmux_errors() {
+        let mut reader = "%begin 1 12 1\ncan't find window: 9\n%error 1 12 1\n".as_bytes();
+        assert_eq!(
+            read_reply(&mut reader).unwrap(),
+            Reply::Failed(vec!["can't find window: 9".into()])
+        );
+    }
+
+    #[test]
+    fn errors_when_the_client_exits() {
+        let mut reader = "%exit\n".as_bytes();
+        assert!(read_reply(&mut reader).is_err());
+    }
+}
@@ -1,14 +1,22 @@
+use crate::shell;
+use crate::tmux::TMUX_NAME;
 use common::error::CommonError;
-use std::ffi::NulError;
+use std::process::ExitStatus;
 use std::{fmt, io};
 
 #[derive(Debug)]
 pub enum TmuxError {
     Io(io::Error),
     Common(CommonError),
-    Attach(NulError),
+    Attach(String, ExitStatus),
     Pre,
     Config,
+    Window(String),
+    CommandFailed {
+        args: Vec<String>,
+        status: Option<i32>,
+        stderr: String,
+    },
 }
 
 impl fmt::Display for TmuxError {
@@ -16,9 +24,31 @@ impl fmt::Display for TmuxError {
         match self {
             TmuxError::Io(e) => write!(f, "IO error: {}", e),
             TmuxError::Common(e) => write!(f, "{}", e),
-            TmuxError::Attach(e) => write!(f, "Couldn't attach to TMUX: {}", e),
+            TmuxError::Attach(session, status) => write!(
+                f,
+                "Couldn't attach to the session `{}`, tmux exited with {}",
+                session, status
+            ),
             TmuxError::Pre => write!(f, "Couldn't find args for pre option"),
             TmuxError::Config => write!(f, "Couldn't get tmux options"),
+            TmuxError::CommandFailed {
+                args,
+                status,
+                stderr,
+            } => {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                write!(f, "`{}` failed", shell::line(TMUX_NAME, &args))?;
+                if let Some(code) = status {
+                    write!(f, " with exit code {}", code)?;
+                }
+                match stderr.is_empty() {
+                    true => Ok(()),
+                    false => write!(f, ": {}", stderr),
+                }
+            }
+            TmuxError::Window(session) => {
+                write!(f, "Couldn't list the windows of the session `{}`", session)
+            }
         }
     }
 }
@@ -36,9 +66,3 @@ impl From<io::Error> for TmuxError {
         TmuxError::Io(err)
     }
 }
-
-impl From<NulError> for TmuxError {
-    fn from(err: NulError) -> TmuxError {
-        TmuxError::Attach(err)
-    }
-}
@@ -1,22 +1,22 @@
 //! The interface for interacting with TMUX sessions. All the commands that are
 /// built up during the parsing phase get matched to functions here. The
-/// functions in this module all build up strings the get passed to a private
-/// call. The private call converts them to `CStrings` and makes an "unsafe" system
-/// call. All functions go through this `call` function as a common gateway to
-/// system calls and can all be easily logged there.
+/// functions in this module all build up args that get passed to tmux directly,
+/// without a shell in between, so names and paths are never re-parsed. All
+/// functions go through this `call` function as a common gateway to system
+/// calls and can all be easily logged there.
+pub mod control;
 pub mod error;
 pub mod target;
 
+use crate::project::TMUX_ENV_VAR;
 use crate::tmux::error::TmuxError;
-use libc::system;
-use std::ffi::CString;
-use std::os::unix::process::ExitStatusExt;
-use std::process::{Command, ExitStatus, Output};
+use std::env;
+use std::process::{Command, Output, Stdio};
 
 type Result<T> = std::result::Result<T, TmuxError>;
 
 /// The program to call commands on.
-static TMUX_NAME: &str = "tmux";
+pub static TMUX_NAME: &str = "tmux";
 
 /// The gateway to calling any functions on tmux. Most public functions in this
 /// module will be fed through this `call` function. This safely creates a new
@@ -63,6 +63,72 @@ pub fn has_session(target: &str) -> bool {
     }
 }
 
+/// The name of the session the client muxed runs from is attached to. `None`
+/// when muxed isn't running inside tmux.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate load;
+/// use load::tmux;
+///
+/// let _ = tmux::current_session();
+/// ```
+pub fn current_session() -> Option<String> {
+    env::var_os(TMUX_ENV_VAR)?;
+
+    let output = call(&["display-message", "-p", "#{session_name}"]).ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+/// Whether the foreground process of a pane is its shell, tmux's
+/// `default-shell`, so nothing is left running in it. A pane that's gone has
+/// nothing running in it either.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate load;
+/// use load::tmux;
+///
+/// let idle = tmux::pane_idle("muxed:0.0");
+/// ```
+pub fn pane_idle(target: &str) -> bool {
+    let format = "#{==:#{pane_current_command},#{b:default-shell}}";
+
+    match call(&["display-message", "-p", "-t", target, format]) {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim() == "1"
+        }
+        _ => true,
+    }
+}
+
+/// The indices of a running session's windows.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate load;
+/// use load::tmux;
+///
+/// assert!(tmux::window_indices("muxed-abc-123").is_err());
+/// ```
+pub fn window_indices(session: &str) -> Result<Vec<usize>> {
+    let output = call(&["list-windows", "-t", session, "-F", "#{window_index}"])?;
+
+    if !output.status.success() {
+        return Err(TmuxError::Window(session.to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
 /// Read the tmux config and return a config object
 ///
 /// # Examples
@@ -89,31 +155,52 @@ pub fn get_config() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Read the tmux config from a server that is already running. Unlike
+/// `get_config` this never starts a server, so it returns an error when no
+/// server is around to ask.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate load;
+/// use load::tmux;
+///
+/// let options = tmux::get_running_config().unwrap_or_default();
+/// ```
+pub fn get_running_config() -> Result<String> {
+    let output = call(&["show-options", "-g", ";", "show-options", "-g", "-w"])
+        .map_err(|_| TmuxError::Config)?;
+
+    if !output.status.success() {
+        return Err(TmuxError::Config);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
 /// Attach is called as the last function in a set of commands. After the tmux
 /// env has been setup by all previous commands this attaches the user to their
-/// daemonized tmux session.
+/// daemonized tmux session. Unlike `call` the client shares muxed's terminal,
+/// and muxed waits for it to detach. tmux's own output is discarded.
 ///
 /// # Examples
 ///
-/// `session_name: The active tmux session name.
-///
 /// ```rust,no_run
 /// extern crate load;
 /// use load::tmux;
 ///
-/// tmux::attach(&["muxed"]);
+/// tmux::attach(&["attach", "-t", "muxed"]);
 /// ```
 pub fn attach(args: &[&str]) -> Result<Output> {
-    let arg_string = [&[TMUX_NAME], args].concat().join(" ");
-    let system_call = CString::new(arg_string).map_err(TmuxError::Attach)?;
-    // println!("{:?}", arg_string.clone());
-    unsafe {
-        let output = system(system_call.as_ptr());
-
-        Ok(Output {
-            status: ExitStatus::from_raw(output),
-            stdout: vec![],
-            stderr: vec![],
-        })
-    }
+    let status = Command::new(TMUX_NAME)
+        .args(args)
+        .stdout(Stdio::null())
+        .status()
+        .map_err(TmuxError::Io)?;
+
+    Ok(Output {
+        status,
+        stdout: vec![],
+        stderr: vec![],
+    })
 }
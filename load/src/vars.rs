@@ -0,0 +1,340 @@
+//! Template variables for project files. Placeholders written as `${name}` or
+//! `{{name}}` are replaced with values from the command line, the project's
+//! `vars:` map, or the environment, in that order of priority.
+//!
+//! Only names made of letters, digits and underscores are treated as
+//! placeholders. Anything else, like the shell's `${name:-default}`, is left
+//! alone for the shell to deal with.
+
+use common::tmux::{Pane, Pre, Session};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The sources a placeholder can be resolved from.
+pub struct Vars<'a> {
+    overrides: HashMap<&'a str, &'a str>,
+    defaults: BTreeMap<String, String>,
+}
+
+impl<'a> Vars<'a> {
+    /// Build the lookup from `key=value` command line arguments and the
+    /// project's `vars:` map. Returns the offending argument if one isn't in
+    /// the `key=value` format.
+    pub fn new(
+        args: &'a [String],
+        defaults: Option<BTreeMap<String, String>>,
+    ) -> Result<Vars<'a>, &'a str> {
+        let overrides = args
+            .iter()
+            .map(|arg| match arg.split_once('=') {
+                Some((key, value)) if is_name(key) => Ok((key, value)),
+                _ => Err(arg.as_str()),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Vars {
+            overrides,
+            defaults: defaults.unwrap_or_default(),
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.overrides
+            .get(name)
+            .map(|value| value.to_string())
+            .or_else(|| self.defaults.get(name).cloned())
+            .or_else(|| env::var(name).ok())
+    }
+
+    /// Replace every placeholder in `input`. Returns the name of the first
+    /// variable that has no value.
+    pub fn substitute(&self, input: &str) -> Result<String, String> {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some((start, open, close)) = next_placeholder(rest) {
+            let after = &rest[start + open.len()..];
+
+            match after.find(close) {
+                Some(end) if is_name(after[..end].trim()) => {
+                    let name = after[..end].trim();
+                    let value = self.get(name).ok_or_else(|| name.to_string())?;
+
+                    output.push_str(&rest[..start]);
+                    output.push_str(&value);
+                    rest = &after[end + close.len()..];
+                }
+                _ => {
+                    output.push_str(&rest[..start + open.len()]);
+                    rest = after;
+                }
+            }
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn substitute_path(&self, path: &Path) -> Result<PathBuf, String> {
+        self.substitute(&path.to_string_lossy()).map(PathBuf::from)
+    }
+
+    fn substitute_pre(&self, pre: &Pre) -> Result<Pre, String> {
+        pre.iter()
+            .map(|cmd| self.substitute(cmd))
+            .collect::<Result<_, _>>()
+            .map(Pre)
+    }
+}
+
+/// Find the earliest `${` or `{{` in the input, returning its position along
+/// with its opening and closing delimiters.
+fn next_placeholder(input: &str) -> Option<(usize, &'static str, &'static str)> {
+    let dollar = input.find("${").map(|i| (i, "${", "}"));
+    let braces = input.find("{{").map(|i| (i, "{{", "}}"));
+
+    match (dollar, braces) {
+        (Some(d), Some(b)) => Some(if d.0 <= b.0 { d } else { b }),
+        (d, b) => d.or(b),
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Resolve every placeholder in the parts of a session that end up in tmux:
+/// the session name and root, `pre`, `pre_window`, `pre_pane`, `post` and
+/// `on_stop`, each window's name, path, command and `pre`, and each pane's,
+/// nested ones too, path, command, `pre` and `keys`. Returns the name of the
+/// first variable that has no value.
+pub fn interpolate(session: &mut Session, vars: &Vars) -> Result<(), String> {
+    if let Some(name) = session.name.as_ref() {
+        session.name = Some(vars.substitute(name)?);
+    }
+
+    if let Some(root) = session.root.as_ref() {
+        session.root = Some(vars.substitute_path(root)?);
+    }
+
+    if let Some(pre) = session.pre.as_ref() {
+        session.pre = Some(vars.substitute_pre(pre)?);
+    }
+
+    if let Some(pre) = session.pre_window.as_ref() {
+        session.pre_window = Some(vars.substitute_pre(pre)?);
+    }
+
+    if let Some(pre) = session.pre_pane.as_ref() {
+        session.pre_pane = Some(vars.substitute_pre(pre)?);
+    }
+
+    if let Some(post) = session.post.as_ref() {
+        session.post = Some(vars.substitute_pre(post)?);
+    }
+
+    if let Some(on_stop) = session.on_stop.as_ref() {
+        session.on_stop = Some(vars.substitute_pre(on_stop)?);
+    }
+
+    for window in session.windows.iter_mut() {
+        window.name = vars.substitute(&window.name)?;
+
+        if let Some(path) = window.path.as_ref() {
+            window.path = Some(vars.substitute_path(path)?);
+        }
+
+        if let Some(command) = window.command.as_ref() {
+            window.command = Some(vars.substitute(command)?);
+        }
+
+        if let Some(pre) = window.pre.as_ref() {
+            window.pre = Some(vars.substitute_pre(pre)?);
+        }
+
+        for pane in window.panes.iter_mut() {
+            interpolate_pane(pane, vars)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve the placeholders in a pane and every pane nested in it.
+fn interpolate_pane(pane: &mut Pane, vars: &Vars) -> Result<(), String> {
+    if let Some(path) = pane.path.as_ref() {
+        pane.path = Some(vars.substitute_path(path)?);
+    }
+
+    if let Some(command) = pane.command.as_ref() {
+        pane.command = Some(vars.substitute(command)?);
+    }
+
+    if let Some(pre) = pane.pre.as_ref() {
+        pane.pre = Some(vars.substitute_pre(pre)?);
+    }
+
+    for keys in pane.keys.iter_mut() {
+        *keys = vars.substitute(keys)?;
+    }
+
+    for pane in pane.panes.iter_mut() {
+        interpolate_pane(pane, vars)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::Window;
+
+    fn vars<'a>(args: &'a [String], defaults: &[(&str, &str)]) -> Vars<'a> {
+        let defaults = defaults
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Vars::new(args, Some(defaults)).unwrap()
+    }
+
+    #[test]
+    fn substitutes_both_placeholder_styles() {
+        let vars = vars(&[], &[("service", "billing")]);
+        let result = vars.substitute("cd ~/src/${service} && {{ service }}");
+        assert_eq!(result.unwrap(), "cd ~/src/billing && billing");
+    }
+
+    #[test]
+    fn command_line_overrides_vars_map() {
+        let args = vec!["service=billing".to_string()];
+        let vars = vars(&args, &[("service", "auth")]);
+        assert_eq!(vars.substitute("${service}").unwrap(), "billing");
+    }
+
+    #[test]
+    fn falls_back_to_the_environment() {
+        let vars = vars(&[], &[]);
+        let home = env::var("HOME").unwrap();
+        assert_eq!(vars.substitute("${HOME}").unwrap(), home);
+    }
+
+    #[test]
+    fn errors_with_the_unresolved_name() {
+        let vars = vars(&[], &[]);
+        let result = vars.substitute("echo {{muxed_not_a_var}}");
+        assert_eq!(result.unwrap_err(), "muxed_not_a_var");
+    }
+
+    #[test]
+    fn leaves_shell_syntax_alone() {
+        let vars = vars(&[], &[]);
+        let input = "echo ${muxed_nope:-fallback} $HOME {{ not closed";
+        assert_eq!(vars.substitute(input).unwrap(), input);
+    }
+
+    #[test]
+    fn rejects_malformed_command_line_vars() {
+        let args = vec!["service".to_string()];
+        assert_eq!(Vars::new(&args, None).err(), Some("service"));
+    }
+
+    #[test]
+    fn interpolates_the_session() {
+        let mut session = Session {
+            root: Some(PathBuf::from("~/src/${service}")),
+            pre: Some(Pre(vec!["docker start {{service}}".into()])),
+            windows: vec![Window {
+                name: "${service}".into(),
+                command: Some("make ${service}".into()),
+                panes: vec![Pane {
+                    command: Some("tail -f {{service}}.log".into()),
+                    path: Some(PathBuf::from("logs/${service}")),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let args = vec!["service=billing".to_string()];
+        interpolate(&mut session, &vars(&args, &[])).unwrap();
+
+        assert_eq!(session.root, Some(PathBuf::from("~/src/billing")));
+        assert_eq!(session.pre, Some(Pre(vec!["docker start billing".into()])));
+
+        let window = &session.windows[0];
+        assert_eq!(window.name, "billing");
+        assert_eq!(window.command.as_deref(), Some("make billing"));
+        assert_eq!(
+            window.panes[0].command.as_deref(),
+            Some("tail -f billing.log")
+        );
+        assert_eq!(window.panes[0].path, Some(PathBuf::from("logs/billing")));
+    }
+
+    #[test]
+    fn interpolates_nested_panes() {
+        let mut session = Session {
+            windows: vec![Window {
+                name: "api".into(),
+                panes: vec![
+                    Pane::default(),
+                    Pane {
+                        panes: vec![Pane {
+                            command: Some("psql ${db}".into()),
+                            path: Some(PathBuf::from("db/{{db}}")),
+                            pre: Some(Pre(vec!["export PGDATABASE=${db}".into()])),
+                            keys: vec!["${key}".into()],
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let args = vec!["db=billing".to_string(), "key=C-l".to_string()];
+        interpolate(&mut session, &vars(&args, &[])).unwrap();
+
+        let nested = &session.windows[0].panes[1].panes[0];
+        assert_eq!(nested.command.as_deref(), Some("psql billing"));
+        assert_eq!(nested.path, Some(PathBuf::from("db/billing")));
+        assert_eq!(
+            nested.pre,
+            Some(Pre(vec!["export PGDATABASE=billing".into()]))
+        );
+        assert_eq!(nested.keys, vec!["C-l".to_string()]);
+    }
+
+    #[test]
+    fn errors_on_unresolved_vars_in_nested_panes() {
+        let mut session = Session {
+            windows: vec![Window {
+                name: "api".into(),
+                panes: vec![Pane {
+                    panes: vec![Pane {
+                        command: Some("psql ${muxed_no_db}".into()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let result = interpolate(&mut session, &vars(&[], &[]));
+        assert_eq!(result.unwrap_err(), "muxed_no_db");
+    }
+}
@@ -0,0 +1,143 @@
+//! Checks the backends `load` can send a project's commands to tmux with
+//! build the same session and report the commands tmux fails to run alike,
+//! and benchmarks them.
+
+extern crate common;
+extern crate load;
+extern crate rand;
+extern crate snapshot;
+
+#[cfg(test)]
+mod test {
+    use common::args::Args;
+    use common::tmux::{Session, Target};
+    use load::backend::{self, Backend, Policy};
+    use load::command::{Commands, SelectWindow, Session as NewSession};
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    static WINDOWS: usize = 12;
+    static PANES: usize = 3;
+
+    fn contents(name: &str) -> String {
+        let mut contents = format!("---\nname: {}\nwindows:\n", name);
+        for window in 0..WINDOWS {
+            contents.push_str(&format!(
+                "  - window{}:\n      layout: main-vertical\n      panes:\n",
+                window
+            ));
+            for pane in 0..PANES {
+                contents.push_str(&format!("        - echo {}.{}\n", window, pane));
+            }
+        }
+        contents
+    }
+
+    /// Build the same project with the backend, timing only the tmux calls.
+    fn build(backend: Backend) -> (Duration, Session) {
+        let name = format!("muxed-bench-{}", random::<u16>());
+        let dir = temp_dir().join(&name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{}.yml", name)), contents(&name)).unwrap();
+
+        let args = Args {
+            arg_project: name.clone(),
+            flag_p: Some(dir.display().to_string()),
+            ..Default::default()
+        };
+        let (_, commands) = load::plan(&args, true).unwrap();
+
+        let start = Instant::now();
+        backend::run(&commands, backend, &name, Policy::Abort).unwrap();
+        let elapsed = start.elapsed();
+
+        let session = snapshot::inspect(&name).unwrap();
+        let _ = load::tmux::call(&["kill-session", "-t", &name]);
+        let _ = fs::remove_dir_all(&dir);
+
+        (elapsed, session)
+    }
+
+    fn shape(session: &Session) -> Vec<(String, usize)> {
+        session
+            .windows
+            .iter()
+            .map(|window| (window.name.clone(), window.panes.len()))
+            .collect()
+    }
+
+    #[test]
+    fn batch_and_control_build_the_same_session_as_each() {
+        let (_, each) = build(Backend::Each);
+        let (_, batch) = build(Backend::Batch);
+        let (_, control) = build(Backend::Control);
+
+        assert_eq!(shape(&each).len(), WINDOWS);
+        assert_eq!(shape(&each), shape(&batch));
+        assert_eq!(shape(&each), shape(&control));
+    }
+
+    /// Timings depend on the machine and whatever else the tmux server is
+    /// doing, so they're only printed. Run with
+    /// `cargo test -p load --test backend -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_backends() {
+        let (each, _) = build(Backend::Each);
+        let (batch, _) = build(Backend::Batch);
+        let (control, _) = build(Backend::Control);
+
+        println!(
+            "{} windows of {} panes: each {:?}, batch {:?}, control {:?}",
+            WINDOWS, PANES, each, batch, control
+        );
+    }
+
+    /// Run a plan whose second and fourth commands fail, returning the index
+    /// and error of each failure.
+    fn fail(backend: Backend, policy: Policy) -> Vec<(usize, String)> {
+        let name = format!("muxed-bench-{}", random::<u16>());
+        let missing = Target::new(name.as_str(), Some(7), None);
+        let commands: Vec<Commands> = vec![
+            NewSession::new(name.as_str(), "editor", None).into(),
+            SelectWindow::new(missing.clone()).into(),
+            SelectWindow::new(Target::new(name.as_str(), Some(0), None)).into(),
+            SelectWindow::new(missing).into(),
+        ];
+
+        let result = backend::run(&commands, backend, &name, policy);
+        let _ = load::tmux::call(&["kill-session", "-t", &name]);
+
+        result
+            .unwrap_err()
+            .into_iter()
+            .map(|failure| (failure.index, failure.error.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn every_backend_reports_the_command_tmux_rejected() {
+        for backend in [Backend::Each, Backend::Batch, Backend::Control] {
+            let failures = fail(backend, Policy::Abort);
+            assert_eq!(failures.len(), 1, "{:?}", backend);
+
+            let (index, error) = &failures[0];
+            assert_eq!(*index, 1, "{:?}", backend);
+            assert!(error.contains("select-window"), "{}", error);
+            assert!(error.contains("can't find window: 7"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn every_backend_keeps_going_past_failures() {
+        for backend in [Backend::Each, Backend::Batch, Backend::Control] {
+            let indices: Vec<usize> = fail(backend, Policy::KeepGoing)
+                .into_iter()
+                .map(|(index, _)| index)
+                .collect();
+            assert_eq!(indices, vec![1, 3], "{:?}", backend);
+        }
+    }
+}
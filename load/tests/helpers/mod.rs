@@ -46,10 +46,35 @@ fn cleanup(project_name: &str, config_path: &PathBuf) {
 
 pub fn test_with_contents(contents: &[u8]) -> Session {
     let (project_name, config_path) = setup(contents);
-    let _ = open_muxed(&project_name, config_path.parent().unwrap());
+    let _ = open_muxed(&project_name, config_path.parent().unwrap(), false);
 
+    inspect_and_cleanup(&project_name, &config_path)
+}
+
+/// Load a project, kill the given windows or panes, then load it again with
+/// `--reconcile` so the killed parts are recreated.
+pub fn test_reconcile_with_contents(contents: &[u8], kill: &[&str]) -> Session {
+    let (project_name, config_path) = setup(contents);
+    let _ = open_muxed(&project_name, config_path.parent().unwrap(), false);
+
+    for target in kill {
+        let target = format!("{}:{}", project_name, target);
+        let command = if target.contains('.') {
+            "kill-pane"
+        } else {
+            "kill-window"
+        };
+        let _ = load::tmux::call(&[command, "-t", &target]);
+    }
+
+    let _ = open_muxed(&project_name, config_path.parent().unwrap(), true);
+
+    inspect_and_cleanup(&project_name, &config_path)
+}
+
+fn inspect_and_cleanup(project_name: &str, config_path: &PathBuf) -> Session {
     // Disable history in the test session to avoid polluting the user's shell history
-    send_keys(&project_name, " unset HISTFILE");
+    send_keys(project_name, " unset HISTFILE");
 
     let completed = PathBuf::from(format!(
         "/tmp/{}-{}.complete",
@@ -58,19 +83,20 @@ pub fn test_with_contents(contents: &[u8]) -> Session {
     ));
     let exec = format!("touch '{}'", completed.display());
 
-    send_keys(&project_name, &exec);
+    send_keys(project_name, &exec);
     wait_on(&completed);
 
-    let session = snapshot::inspect(&project_name).unwrap();
-    cleanup(&project_name, &config_path);
+    let session = snapshot::inspect(project_name).unwrap();
+    cleanup(project_name, config_path);
     session
 }
 
-fn open_muxed(project: &str, project_root: &Path) -> Result<(), String> {
+fn open_muxed(project: &str, project_root: &Path, reconcile: bool) -> Result<(), String> {
     let args = Args {
         arg_project: project.to_string(),
         flag_p: Some(format!("{}", project_root.display())),
         flag_debug: true,
+        flag_reconcile: reconcile,
         ..Default::default()
     };
 
@@ -87,7 +113,7 @@ fn send_keys(target: &str, exec: &str) {
     let _ = load::tmux::call(&["send-keys", "-t", target, &spaced, "KPEnter"]);
 }
 
-fn wait_on(file: &PathBuf) {
+fn wait_on(file: &Path) {
     while !file.exists() {
         // Wait increased from 10 to 750 due to the pre_window tests.
         sleep(Duration::from_millis(750));
@@ -4,7 +4,6 @@ extern crate common;
 extern crate snapshot;
 
 extern crate dirs;
-extern crate libc;
 extern crate load;
 extern crate rand;
 extern crate retry_test;
@@ -15,7 +14,7 @@ mod helpers;
 #[cfg(test)]
 mod test {
     mod load {
-        use crate::helpers::test_with_contents;
+        use crate::helpers::{test_reconcile_with_contents, test_with_contents};
         use common::project_paths::homedir;
         use common::rand_names;
         use dirs::home_dir;
@@ -232,7 +231,7 @@ windows: ['ssh', 'git']
             let other_window = &session.windows[1];
 
             assert!(first_window.active);
-            assert_eq!(other_window.active, false)
+            assert!(!other_window.active)
         }
 
         #[test]
@@ -335,8 +334,26 @@ windows: ['ssh', 'git']
         ";
             let session = test_with_contents(contents);
             let window = session.find_window_by_name("ssh").unwrap();
-            assert_eq!(window.panes[0].active, true);
-            assert_eq!(window.panes[1].active, false);
+            assert!(window.panes[0].active);
+            assert!(!window.panes[1].active);
+        }
+
+        #[test]
+        fn reconcile_recreates_killed_windows_and_panes() {
+            let contents = b"---
+windows:
+  - editor:
+      layout: 'main-vertical'
+      panes: ['ls', 'vi']
+  - git: ''
+  - logs: ''
+";
+            let session = test_reconcile_with_contents(contents, &["0.1", "1"]);
+            assert_eq!(session.windows.len(), 3);
+
+            let window = session.find_window_by_name("editor").unwrap();
+            assert_eq!(window.panes.len(), 2);
+            assert!(session.find_window_by_name("git").is_some());
         }
 
         #[test]
@@ -173,7 +173,7 @@ mod test {
     fn expect_ok_result_when_path_exists() {
         let path = rand_names::project_file_in_tmp_dir();
 
-        let result = write_template(&"test template".to_string(), &path, false);
+        let result = write_template("test template", &path, false);
         let _ = fs::remove_file(path);
         assert!(result.is_ok());
     }
@@ -181,14 +181,14 @@ mod test {
     #[test]
     fn expect_err_result_when_path_does_not_exist() {
         let path = rand_names::project_file_path();
-        let result = write_template(&"test template".to_string(), &path, false);
+        let result = write_template("test template", &path, false);
         assert!(result.is_err());
     }
 
     #[test]
     fn expect_new_file_to_exist() {
         let path = rand_names::project_file_in_tmp_dir();
-        let _ = write_template(&"test template".to_string(), &path, false);
+        let _ = write_template("test template", &path, false);
         let result = &path.exists();
         let _ = fs::remove_file(&path);
         assert!(result);
@@ -197,7 +197,7 @@ mod test {
     #[test]
     fn expect_new_file_not_to_exist() {
         let path = rand_names::project_file_path();
-        let _ = write_template(&"test template".to_string(), &path, false);
+        let _ = write_template("test template", &path, false);
         assert!(!path.exists());
     }
 
@@ -210,7 +210,7 @@ mod test {
         let _ = buffer.sync_all();
 
         // Attempt to create the same named file with new content
-        let _ = write_template(&"new_content".to_string(), &path, false);
+        let _ = write_template("new_content", &path, false);
 
         let content = fs::read_to_string(&path).unwrap();
 
@@ -227,7 +227,7 @@ mod test {
         let _ = buffer.sync_all();
 
         // Attempt to create the same named file with new content
-        let _ = write_template(&"new content".to_string(), &path, true);
+        let _ = write_template("new content", &path, true);
 
         let content = fs::read_to_string(&path).unwrap();
 
@@ -241,7 +241,7 @@ mod test {
         let mut buffer = File::create(&path).unwrap();
         let _ = buffer.write(b"original content");
         let _ = buffer.sync_all();
-        let result = write_template(&"new content".to_string(), &path, false);
+        let result = write_template("new content", &path, false);
 
         assert!(result.is_err());
         let _ = fs::remove_file(&path);
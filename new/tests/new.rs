@@ -8,7 +8,6 @@ mod test {
     mod new {
         use common::args::Args;
         use common::rand_names;
-        use new;
         use std::fs;
         use std::path::Path;
 
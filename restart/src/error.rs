@@ -0,0 +1,47 @@
+use load::error::LoadError;
+use load::tmux::error::TmuxError;
+use std::fmt;
+use stop::error::StopError;
+
+#[derive(Debug)]
+pub enum RestartError {
+    Holding(String),
+    Load(LoadError),
+    Stop(StopError),
+    Tmux(TmuxError),
+}
+
+impl fmt::Display for RestartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RestartError::Holding(session) => write!(
+                f,
+                "Couldn't create the session `{}` to hold the client while restarting",
+                session
+            ),
+            RestartError::Load(e) => write!(f, "{}", e),
+            RestartError::Stop(e) => write!(f, "{}", e),
+            RestartError::Tmux(e) => write!(f, "Couldn't restart the session: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RestartError {}
+
+impl From<LoadError> for RestartError {
+    fn from(err: LoadError) -> RestartError {
+        RestartError::Load(err)
+    }
+}
+
+impl From<StopError> for RestartError {
+    fn from(err: StopError) -> RestartError {
+        RestartError::Stop(err)
+    }
+}
+
+impl From<TmuxError> for RestartError {
+    fn from(err: TmuxError) -> RestartError {
+        RestartError::Tmux(err)
+    }
+}
@@ -0,0 +1,251 @@
+//! Muxedrestart. Tears down a project's running tmux session and builds it
+//! again from the project file.
+mod error;
+
+extern crate common;
+extern crate libc;
+extern crate load;
+extern crate stop;
+
+use crate::error::RestartError;
+use common::args::Args;
+use common::tmux::Session;
+use load::backend::Backend;
+use load::command::{Commands, KillSession, Session as NewSession, SwitchClient};
+use load::shell;
+use load::tmux;
+
+/// The main execution method.
+/// Reads and plans the project the way `load` would. When its session is
+/// already running it's stopped first, running its `stop_keys` and `on_stop`
+/// hooks, so edits to the project file take effect. A client attached to the
+/// session from inside tmux is parked on a temporary session while the project
+/// is rebuilt and switched back afterwards, instead of being detached. The
+/// session is rebuilt with the backend and policy `load` would use. With
+/// `--dry-run` the commands are printed instead.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed restart my_project
+/// $ ./muxed restart --dry-run my_project
+/// ```
+pub fn restart(args: Args) -> Result<(), RestartError> {
+    let (project, build) = load::plan(&args, args.flag_d)?;
+    let name = project.name();
+
+    let running = tmux::has_session(name);
+    let attached = running && tmux::current_session().as_deref() == Some(name);
+
+    // The backend is checked before anything is torn down.
+    Backend::from_arg(args.flag_backend.as_deref())?;
+
+    let holding = holding_name(name, tmux::has_session);
+    let plan = plan(project.session(), name, &holding, build, running, attached);
+
+    if args.flag_dry_run {
+        for command in plan.commands() {
+            println!("{}", shell::render(command));
+        }
+        return Ok(());
+    }
+
+    if attached {
+        // muxed is running in one of the session's panes, so killing the
+        // session hangs it up and stop keys can interrupt it. Carry on
+        // regardless, the client is parked on the holding session by then.
+        unsafe {
+            libc::signal(libc::SIGHUP, libc::SIG_IGN);
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+        }
+    }
+
+    for command in &plan.park {
+        let output = command.as_trait().call()?;
+
+        // Without a holding session of its own the client would be moved in
+        // to some other session, which is killed once the project is rebuilt.
+        if matches!(command, Commands::Session(_)) && !output.status.success() {
+            return Err(RestartError::Holding(holding));
+        }
+    }
+
+    if running {
+        stop::run(project.session(), name)?;
+    }
+
+    load::execute(&args, &project, &plan.build)?;
+
+    for command in &plan.unpark {
+        command.as_trait().call()?;
+    }
+
+    Ok(())
+}
+
+/// The name of a session for a client to wait in while its session is
+/// rebuilt, `<name>-restarting`, numbered when a session is `taken` by that
+/// name already.
+fn holding_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let base = format!("{}-restarting", name);
+    let mut holding = base.clone();
+    let mut number = 2;
+
+    while taken(&holding) {
+        holding = format!("{}-{}", base, number);
+        number += 1;
+    }
+
+    holding
+}
+
+/// The commands of a restart, in the order they run.
+struct Plan {
+    /// Move an attached client to the holding session.
+    park: Vec<Commands>,
+    /// Tear down the running session.
+    stop: Vec<Commands>,
+    /// Build the session again, the way `load` would.
+    build: Vec<Commands>,
+    /// Move the client back and kill the holding session.
+    unpark: Vec<Commands>,
+}
+
+impl Plan {
+    fn commands(&self) -> impl Iterator<Item = &Commands> {
+        self.park
+            .iter()
+            .chain(&self.stop)
+            .chain(&self.build)
+            .chain(&self.unpark)
+    }
+}
+
+/// Wrap the commands that build a session with the ones that tear down the
+/// running one. When the client is `attached` to it, it's moved to a holding
+/// session before the teardown and switched back to the rebuilt session in
+/// place of attaching, even when daemonized, since it never meant to leave.
+fn plan(
+    session: &Session,
+    name: &str,
+    holding: &str,
+    build: Vec<Commands>,
+    running: bool,
+    attached: bool,
+) -> Plan {
+    let mut plan = Plan {
+        park: vec![],
+        stop: vec![],
+        build,
+        unpark: vec![],
+    };
+
+    if running {
+        plan.stop = stop::plan(session, name);
+    }
+
+    if attached {
+        plan.park = vec![
+            NewSession::new(holding, holding, None).into(),
+            SwitchClient::new(holding).into(),
+        ];
+        plan.build
+            .retain(|command| !matches!(command, Commands::Attach(_)));
+        plan.unpark = vec![
+            SwitchClient::new(name).into(),
+            KillSession::new(holding).into(),
+        ];
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Pre, Target};
+    use load::command::Attach;
+
+    fn args(plan: &Plan) -> Vec<String> {
+        plan.commands()
+            .map(|command| command.as_trait().args().join(" "))
+            .collect()
+    }
+
+    fn build() -> Vec<Commands> {
+        vec![
+            NewSession::new("muxed", "editor", None).into(),
+            Attach::new(Target::new("muxed", None, None), None).into(),
+        ]
+    }
+
+    #[test]
+    fn only_builds_when_nothing_is_running() {
+        let plan = plan(
+            &Session::default(),
+            "muxed",
+            "muxed-restarting",
+            build(),
+            false,
+            false,
+        );
+        assert_eq!(
+            args(&plan),
+            vec!["new -d -s muxed -n editor", "attach -t muxed"]
+        );
+    }
+
+    #[test]
+    fn stops_the_running_session_before_building() {
+        let session = Session {
+            on_stop: Some(Pre(vec!["docker compose down".into()])),
+            ..Default::default()
+        };
+        let plan = plan(&session, "muxed", "muxed-restarting", build(), true, false);
+        assert_eq!(
+            args(&plan),
+            vec![
+                "run-shell -t muxed docker compose down",
+                "kill-session -t muxed",
+                "new -d -s muxed -n editor",
+                "attach -t muxed",
+            ]
+        );
+    }
+
+    #[test]
+    fn parks_an_attached_client_while_rebuilding() {
+        let plan = plan(
+            &Session::default(),
+            "muxed",
+            "muxed-restarting",
+            build(),
+            true,
+            true,
+        );
+        assert_eq!(
+            args(&plan),
+            vec![
+                "new -d -s muxed-restarting -n muxed-restarting",
+                "switch-client -t muxed-restarting",
+                "kill-session -t muxed",
+                "new -d -s muxed -n editor",
+                "switch-client -t muxed",
+                "kill-session -t muxed-restarting",
+            ]
+        );
+    }
+
+    #[test]
+    fn holds_the_client_in_a_session_that_isnt_running() {
+        assert_eq!(holding_name("muxed", |_| false), "muxed-restarting");
+
+        let running = ["muxed-restarting", "muxed-restarting-2"];
+        assert_eq!(
+            holding_name("muxed", |name| running.contains(&name)),
+            "muxed-restarting-3"
+        );
+    }
+}
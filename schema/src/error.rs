@@ -0,0 +1,22 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Json(e) => write!(f, "Couldn't write the schema: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(err: serde_json::Error) -> SchemaError {
+        SchemaError::Json(err)
+    }
+}
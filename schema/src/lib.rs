@@ -0,0 +1,77 @@
+//! Muxedschema. Describes the project file format as a JSON Schema.
+mod error;
+
+extern crate common;
+
+use crate::error::SchemaError;
+use common::tmux::Session;
+use schemars::schema_for;
+
+/// The main execution method.
+/// Prints the JSON Schema for project files. It's generated from the same
+/// types project files are read in to, so it always matches what muxed
+/// accepts.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed schema > ~/.muxed/.schema.json
+/// ```
+pub fn schema() -> Result<(), SchemaError> {
+    println!("{}", to_json()?);
+    Ok(())
+}
+
+fn to_json() -> Result<String, SchemaError> {
+    let mut schema = schema_for!(Session);
+    schema.insert("title".into(), "Muxed project".into());
+
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn schema() -> Value {
+        serde_json::from_str(&to_json().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn shipped_schema_is_current() {
+        let shipped = include_str!("../muxed.schema.json");
+        assert_eq!(
+            shipped.trim_end(),
+            to_json().unwrap(),
+            "Regenerate it with `muxed schema > schema/muxed.schema.json`"
+        );
+    }
+
+    #[test]
+    fn leaves_out_internal_fields() {
+        let properties = &schema()["properties"];
+        assert!(properties.get("target").is_none());
+        assert!(properties.get("config").is_none());
+    }
+
+    #[test]
+    fn accepts_every_window_shape() {
+        let schema = schema();
+        let shapes = schema["$defs"]["Window"]["anyOf"].as_array().unwrap();
+
+        assert_eq!(shapes.len(), 4);
+        assert_eq!(shapes[0]["type"], json!("string"));
+        assert_eq!(shapes[1]["type"], json!("integer"));
+        assert_eq!(shapes[2]["$ref"], json!("#/$defs/DirectWindow"));
+        assert_eq!(shapes[3]["maxProperties"], json!(1));
+    }
+
+    #[test]
+    fn accepts_every_pane_shape() {
+        let pane = &schema()["$defs"]["Pane"]["anyOf"];
+        assert_eq!(pane.as_array().unwrap().len(), 2);
+    }
+}
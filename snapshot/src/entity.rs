@@ -1,8 +1,8 @@
 use crate::error::SnapshotError;
+use crate::foreground::{self, Evidence};
 use common::tmux::{Pane, Target, Window};
 use serde::{Deserialize, Deserializer};
 use std::path::PathBuf;
-use sysinfo::{Pid, Process, System};
 
 fn bool_from_int<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
 where
@@ -33,6 +33,15 @@ pub(crate) enum Entity {
         active: bool,
         path: PathBuf,
         pid: usize,
+        #[serde(default)]
+        current_command: Option<String>,
+        /// Read from after the JSON, see `session_data::parse_line`.
+        #[serde(skip)]
+        start_command: Option<String>,
+        #[serde(default)]
+        width: Option<u32>,
+        #[serde(default)]
+        height: Option<u32>,
     },
 }
 
@@ -43,6 +52,18 @@ impl Entity {
             Entity::Pane { index, .. } => *index,
         }
     }
+
+    /// A pane's width and height, when tmux reported them.
+    pub(crate) fn size(&self) -> Option<(u32, u32)> {
+        match self {
+            Entity::Pane {
+                width: Some(width),
+                height: Some(height),
+                ..
+            } => Some((*width, *height)),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<&Entity> for Window {
@@ -66,6 +87,8 @@ impl TryFrom<&Entity> for Window {
                     name: name.clone(),
                     panes: vec![],
                     path: None,
+                    pre: None,
+                    stop_keys: vec![],
                     target,
                 })
             }
@@ -86,33 +109,29 @@ impl TryFrom<&Entity> for Pane {
                 active,
                 path,
                 pid,
+                current_command,
+                start_command,
+                ..
             } => {
                 let target = Some(Target::new(session, Some(*window_index), Some(*index)));
 
-                // Create a new System and refresh processes
-                let mut system = System::new_all();
-                system.refresh_all();
-
-                let pid = Pid::from(*pid);
-                let mut command: Option<String> = None;
-                if let Some(shell_process) = system.process(pid) {
-                    // Recursively descend into children
-                    if let Some(inner_process) = find_foreground_process(&system, shell_process) {
-                        command = Some(
-                            inner_process
-                                .cmd()
-                                .iter()
-                                .map(|s| s.to_string_lossy()) // gracefully handles invalid UTF-8
-                                .collect::<Vec<_>>()
-                                .join(" "),
-                        )
-                    }
-                }
+                let evidence = Evidence {
+                    current_command: current_command.as_deref(),
+                    start_command: start_command.as_deref(),
+                };
+                let command = foreground::command(*pid, &evidence);
 
                 Ok(Pane {
                     active: *active,
                     command,
+                    keys: vec![],
+                    panes: vec![],
                     path: Some(path.clone()),
+                    pre: None,
+                    scrollback: None,
+                    size: None,
+                    split: None,
+                    stop_keys: vec![],
                     target,
                 })
             }
@@ -121,30 +140,6 @@ impl TryFrom<&Entity> for Pane {
     }
 }
 
-fn find_foreground_process<'a>(sys: &'a System, proc: &'a Process) -> Option<&'a Process> {
-    // Collect immediate children
-    let children: Vec<_> = sys
-        .processes()
-        .values()
-        .filter(|p| p.parent() == Some(proc.pid()))
-        .collect();
-
-    // If there are no children, this shell is idle → return None
-    if children.is_empty() {
-        return None;
-    }
-
-    // Pick the most recently started child
-    let newest = children.into_iter().max_by_key(|p| p.start_time()).unwrap();
-
-    // Recurse into that child — maybe it spawned something else
-    // If it has no children, it’s our leaf
-    match find_foreground_process(sys, newest) {
-        Some(grandchild) => Some(grandchild),
-        None => Some(newest),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,7 +203,9 @@ mod tests {
             "index": 2,
             "active": 0,
             "path": "/tmp",
-            "pid": 12345
+            "pid": 12345,
+            "width": 80,
+            "height": 24
         }"#;
         let ent: Entity = serde_json::from_str(s).unwrap();
         let index_i = ent.index();
@@ -220,6 +217,9 @@ mod tests {
                 active,
                 path,
                 pid,
+                width,
+                height,
+                ..
             } => {
                 assert_eq!(session, "mysess");
                 assert_eq!(window_index, 3);
@@ -227,6 +227,7 @@ mod tests {
                 assert!(!active);
                 assert_eq!(path, PathBuf::from("/tmp"));
                 assert_eq!(pid, 12345);
+                assert_eq!((width, height), (Some(80), Some(24)));
                 assert_eq!(index_i, 2);
             }
             _ => panic!("Expected pane"),
@@ -252,6 +253,10 @@ mod tests {
             active: false,
             path: PathBuf::from("/tmp"),
             pid: 1,
+            current_command: None,
+            start_command: None,
+            width: None,
+            height: None,
         };
         let w2 = Window::try_from(&ent_pane);
         assert!(w2.is_err());
@@ -271,6 +276,10 @@ mod tests {
             active: false,
             path: PathBuf::from("/tmp"),
             pid: 1, // unlikely to find this process, but that's ok for this test
+            current_command: None,
+            start_command: None,
+            width: None,
+            height: None,
         };
         let p = Pane::try_from(&ent);
         assert!(p.is_ok());
@@ -4,11 +4,14 @@ use std::{fmt, io};
 
 #[derive(Debug)]
 pub enum SnapshotError {
+    CaptureFailed(String),
     Common(CommonError),
     Io(io::Error),
     New(NewError),
+    NoSessions,
     SerdeJson(serde_json::Error),
     SessionTargetRequired,
+    SessionsFailed(usize),
     ToPaneFailed,
     ToWindowFailed,
 }
@@ -16,11 +19,22 @@ pub enum SnapshotError {
 impl fmt::Display for SnapshotError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            SnapshotError::CaptureFailed(target) => {
+                write!(
+                    f,
+                    "Failed to capture the scrollback of the pane `{}`",
+                    target
+                )
+            }
             SnapshotError::Common(e) => write!(f, "{}", e),
             SnapshotError::Io(e) => write!(f, "{}", e),
             SnapshotError::New(e) => write!(f, "{}", e),
+            SnapshotError::NoSessions => write!(f, "No tmux sessions are running"),
             SnapshotError::SerdeJson(e) => write!(f, "{}", e),
             SnapshotError::SessionTargetRequired => write!(f, "No TMUX session was provided"),
+            SnapshotError::SessionsFailed(count) => {
+                write!(f, "{} session(s) couldn't be snapshotted", count)
+            }
             SnapshotError::ToPaneFailed => write!(f, "Failed to create pane from snapshot"),
             SnapshotError::ToWindowFailed => write!(f, "Failed to create window from snapshot"),
         }
@@ -0,0 +1,221 @@
+//! Working out the command a pane is running, as it was typed. The process
+//! tree under a pane's shell has whatever the command started, like the
+//! `rustc` under `cargo watch`, so the tree is only walked as far as the first
+//! process that isn't a shell. tmux's view of the pane is trusted first: the
+//! command a pane was started with is the one that was typed, and a pane whose
+//! foreground process is a shell isn't running anything.
+
+use common::tmux::Session;
+use std::path::Path;
+use sysinfo::{Pid, Process, System};
+
+/// Interactive shells, which are walked through to the command run in them.
+static SHELLS: [&str; 12] = [
+    "bash", "csh", "dash", "elvish", "fish", "ksh", "nu", "pwsh", "sh", "tcsh", "xonsh", "zsh",
+];
+
+/// What tmux knows about the command running in a pane.
+#[derive(Debug, Default)]
+pub(crate) struct Evidence<'a> {
+    /// `#{pane_current_command}`, the name of the pane's foreground process.
+    pub(crate) current_command: Option<&'a str>,
+    /// `#{pane_start_command}`, the command the pane was created with.
+    pub(crate) start_command: Option<&'a str>,
+}
+
+/// The command running in the pane whose shell is `pid`.
+pub(crate) fn command(pid: usize, evidence: &Evidence) -> Option<String> {
+    decide(evidence, || {
+        let mut system = System::new_all();
+        system.refresh_all();
+
+        let shell = system.process(Pid::from(pid))?;
+        walk(&system, shell, evidence.current_command)
+    })
+}
+
+/// Weigh tmux's evidence, only walking the process tree when it's not enough.
+fn decide(evidence: &Evidence, walk: impl FnOnce() -> Option<String>) -> Option<String> {
+    if let Some(start) = evidence.start_command.filter(|c| !c.is_empty()) {
+        return Some(start.to_string());
+    }
+
+    match evidence.current_command {
+        Some(current) if is_shell(current) => None,
+        _ => walk(),
+    }
+}
+
+/// Descend from a shell to the first process under it that isn't one. Where a
+/// process has several children the one tmux named as the foreground process
+/// is followed, or else the newest.
+fn walk(system: &System, process: &Process, current: Option<&str>) -> Option<String> {
+    let children: Vec<&Process> = system
+        .processes()
+        .values()
+        .filter(|p| p.parent() == Some(process.pid()))
+        .collect();
+
+    let child = children
+        .iter()
+        .find(|p| current.is_some_and(|current| p.name() == current))
+        .or_else(|| children.iter().max_by_key(|p| p.start_time()))?;
+
+    let cmd: Vec<String> = child
+        .cmd()
+        .iter()
+        .map(|s| s.to_string_lossy().into_owned()) // gracefully handles invalid UTF-8
+        .collect();
+
+    match is_interactive_shell(&cmd) {
+        true => walk(system, child, current),
+        false => Some(cmd.join(" ")).filter(|cmd| !cmd.is_empty()),
+    }
+}
+
+/// Whether a program name is a shell. Login shells are named with a leading
+/// `-`, like `-bash`.
+fn is_shell(name: &str) -> bool {
+    let name = name.trim_start_matches('-');
+    SHELLS.contains(&program(name))
+}
+
+/// A shell running a script or a `-c` command is running a command of its own,
+/// one started with only options is waiting for one to be typed.
+fn is_interactive_shell(cmd: &[String]) -> bool {
+    match cmd.split_first() {
+        Some((shell, args)) => {
+            is_shell(shell) && args.iter().all(|arg| arg.starts_with('-') && arg != "-c")
+        }
+        None => false,
+    }
+}
+
+/// The program a command runs, without its directory.
+fn program(command: &str) -> &str {
+    let first = command.split_whitespace().next().unwrap_or_default();
+    Path::new(first)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(first)
+}
+
+/// Which recorded commands are kept. With an allowlist only commands run by
+/// the programs on it are kept, and commands run by programs on the denylist
+/// never are.
+#[derive(Debug, Default)]
+pub struct Filter {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl Filter {
+    /// Build a filter from comma separated lists of program names.
+    pub fn new(allow: Option<&str>, deny: Option<&str>) -> Filter {
+        let list = |names: Option<&str>| -> Vec<String> {
+            names
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        Filter {
+            allow: list(allow),
+            deny: list(deny),
+        }
+    }
+
+    fn keeps(&self, command: &str) -> bool {
+        let program = program(command).to_string();
+        (self.allow.is_empty() || self.allow.contains(&program)) && !self.deny.contains(&program)
+    }
+
+    /// Drop the pane commands the filter doesn't keep.
+    pub fn apply(&self, session: &mut Session) {
+        for window in &mut session.windows {
+            for pane in &mut window.panes {
+                pane.command = pane.command.take().filter(|c| self.keeps(c));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Pane, Window};
+
+    fn cmd(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn trusts_the_start_command_first() {
+        let evidence = Evidence {
+            current_command: Some("node"),
+            start_command: Some("npm run dev"),
+        };
+        assert_eq!(
+            decide(&evidence, || Some("node server.js".into())).as_deref(),
+            Some("npm run dev")
+        );
+    }
+
+    #[test]
+    fn records_nothing_when_a_shell_is_in_the_foreground() {
+        let evidence = Evidence {
+            current_command: Some("zsh"),
+            start_command: Some(""),
+        };
+        assert_eq!(decide(&evidence, || Some("sleep 100".into())), None);
+    }
+
+    #[test]
+    fn walks_the_tree_otherwise() {
+        let evidence = Evidence {
+            current_command: Some("cargo"),
+            ..Default::default()
+        };
+        assert_eq!(
+            decide(&evidence, || Some("cargo watch -x test".into())).as_deref(),
+            Some("cargo watch -x test")
+        );
+    }
+
+    #[test]
+    fn tells_interactive_shells_from_scripts() {
+        assert!(is_interactive_shell(&cmd(&["bash"])));
+        assert!(is_interactive_shell(&cmd(&["-zsh"])));
+        assert!(is_interactive_shell(&cmd(&["/usr/bin/fish", "--login"])));
+        assert!(!is_interactive_shell(&cmd(&["bash", "deploy.sh"])));
+        assert!(!is_interactive_shell(&cmd(&["sh", "-c", "make watch"])));
+        assert!(!is_interactive_shell(&cmd(&["cargo", "watch"])));
+    }
+
+    #[test]
+    fn filters_commands_by_program() {
+        let pane = |command: &str| Pane {
+            command: Some(command.into()),
+            ..Default::default()
+        };
+        let mut session = Session {
+            windows: vec![Window {
+                panes: vec![pane("/usr/bin/vim src"), pane("rm -rf tmp"), pane("htop")],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        Filter::new(Some("vim, rm"), Some("rm")).apply(&mut session);
+
+        let commands: Vec<_> = session.windows[0]
+            .panes
+            .iter()
+            .map(|pane| pane.command.as_deref())
+            .collect();
+        assert_eq!(commands, vec![Some("/usr/bin/vim src"), None, None]);
+    }
+}
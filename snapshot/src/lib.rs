@@ -6,28 +6,42 @@ extern crate serde;
 extern crate sysinfo;
 
 mod entity;
-mod error;
+pub mod error;
+mod foreground;
+mod scrollback;
 pub mod session_data;
 
 use crate::error::SnapshotError;
+use crate::foreground::Filter;
 use crate::session_data::SessionOutput;
 use common::DEBUG;
 use common::args::Args;
 use common::first_run::check_first_run;
-use common::project_paths::ProjectPaths;
+use common::project_paths::{CONFIG_EXTENSION, ProjectPaths};
 use common::tmux::{Session, Target};
 use new::write_template as write_config;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::result;
 
 static WINDOW_FORMAT: &str = r##"{"type": "window", "session":"#S", "index":#I,"name":"#W","active":#{window_active},"layout":"#{window_layout}"}"##;
-static PANE_FORMAT: &str = r##"{"type": "pane", "session":"#S", "window_index":#I,"index":#P,"active":#{pane_active},"path":"#{pane_current_path}", "pid":#{pane_pid}}"##;
+// tmux prints a pane's start command in its own quoting, bare when it's one
+// word, so it follows the JSON instead of going in it.
+static PANE_FORMAT: &str = concat!(
+    r##"{"type": "pane", "session":"#S", "window_index":#I,"index":#P,"active":#{pane_active},"path":"#{pane_current_path}", "pid":#{pane_pid},"current_command":"#{pane_current_command}","width":#{pane_width},"height":#{pane_height}}"##,
+    " #{pane_start_command}"
+);
 
 type Result<T> = result::Result<T, SnapshotError>;
 
 /// The main execution method.
 /// Accepts two arguments. -n for the name of the project file and -t to target
-/// the session.
+/// the session. With `--with-scrollback` each pane's history is saved next to
+/// the project file and printed in the pane again when the project is loaded.
+/// `--allow` and `--deny` take comma separated programs whose pane commands
+/// are the only ones recorded, or are never recorded.
+/// With `--all` every running session is written to a project file of its
+/// own name instead.
 ///
 /// # Examples
 ///
@@ -41,8 +55,14 @@ type Result<T> = result::Result<T, SnapshotError>;
 ///
 /// ```console
 /// $ ./muxed snapshot -n jasper -t 1
+/// $ ./muxed snapshot --with-scrollback -t 1 jasper
+/// $ ./muxed snapshot --all
 /// ```
 pub fn snapshot(args: Args) -> Result<()> {
+    if args.flag_all {
+        return snapshot_all(&args);
+    }
+
     let session_name = args
         .flag_t
         .as_ref()
@@ -51,15 +71,132 @@ pub fn snapshot(args: Args) -> Result<()> {
 
     check_first_run(&project_paths.project_directory)?;
 
-    let session = inspect(session_name)?;
+    write_snapshot(&args, session_name, &project_paths.project_file)?;
+    println!("We made a snapshot of your session! \u{1F60A}");
+
+    Ok(())
+}
+
+/// Snapshot every running session in to a project file named after it. Files
+/// that already exist are skipped unless `-f` is given. A session that can't
+/// be snapshotted doesn't stop the rest, it's listed with its error and the
+/// run fails once they're all done.
+fn snapshot_all(args: &Args) -> Result<()> {
+    let project_paths = ProjectPaths::try_from(args)?;
+    check_first_run(&project_paths.project_directory)?;
+
+    let sessions = list_sessions()?;
+    if sessions.is_empty() {
+        return Err(SnapshotError::NoSessions);
+    }
+
+    let mut written = vec![];
+    let mut skipped = vec![];
+    let mut failed = vec![];
+
+    for name in sessions {
+        let project_file = project_paths
+            .project_directory
+            .join(&name)
+            .with_extension(CONFIG_EXTENSION);
+
+        if project_file.exists() && !args.flag_f {
+            skipped.push((name, project_file));
+        } else {
+            match write_snapshot(args, &name, &project_file) {
+                Ok(()) => written.push((name, project_file)),
+                Err(e) => failed.push((name, e)),
+            }
+        }
+    }
+
+    print!("{}", summary(&written, &skipped, &failed));
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(SnapshotError::SessionsFailed(failed.len())),
+    }
+}
+
+/// Inspect a session and write it to `project_file`, with its scrollback when
+/// asked for.
+fn write_snapshot(args: &Args, session_name: &str, project_file: &Path) -> Result<()> {
+    let mut session = inspect(session_name)?;
+    Filter::new(args.flag_allow.as_deref(), args.flag_deny.as_deref()).apply(&mut session);
+
+    let captures = match args.flag_with_scrollback {
+        true => scrollback::assign(&mut session, &scrollback::directory(project_file)),
+        false => vec![],
+    };
     let s = serde_saphyr::to_string(&session).unwrap();
 
-    write_config(s, &project_paths.project_file, args.flag_f)?;
-    println!("We made a snapshot of your session! \u{1F60A}");
+    write_config(s, &project_file.to_path_buf(), args.flag_f)?;
+    scrollback::save(&captures)?;
 
     Ok(())
 }
 
+/// The names of the running sessions. No tmux server means no sessions.
+fn list_sessions() -> Result<Vec<String>> {
+    let output = Command::new("tmux")
+        .args(["list-sessions", "-F", "#S"])
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(vec![]);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+fn summary(
+    written: &[(String, PathBuf)],
+    skipped: &[(String, PathBuf)],
+    failed: &[(String, SnapshotError)],
+) -> String {
+    let mut summary = String::new();
+    let list = |sessions: &[(String, PathBuf)]| -> String {
+        sessions
+            .iter()
+            .map(|(name, file)| format!("    {} -> {}\n", name, file.display()))
+            .collect()
+    };
+
+    if !written.is_empty() {
+        summary.push_str(&format!(
+            "We made a snapshot of {} session(s)! \u{1F60A}\n{}",
+            written.len(),
+            list(written)
+        ));
+    }
+
+    if !skipped.is_empty() {
+        summary.push_str(&format!(
+            "Skipped {} session(s) with an existing project file, use -f to overwrite them:\n{}",
+            skipped.len(),
+            list(skipped)
+        ));
+    }
+
+    if !failed.is_empty() {
+        let errors: String = failed
+            .iter()
+            .map(|(name, e)| format!("    {}: {}\n", name, e))
+            .collect();
+        summary.push_str(&format!(
+            "Failed to snapshot {} session(s):\n{}",
+            failed.len(),
+            errors
+        ));
+    }
+
+    summary
+}
+
 pub fn inspect(name: &str) -> result::Result<Session, SnapshotError> {
     let target = Target::new(name, None, None);
     let session_data = session_data(&target)?;
@@ -102,7 +239,7 @@ fn session_data(target: &Target) -> Result<SessionOutput> {
 
 #[cfg(test)]
 mod test {
-    use super::write_config;
+    use super::{SnapshotError, summary, write_config};
     use common::rand_names;
     use std::fs;
     use std::fs::File;
@@ -221,4 +358,24 @@ mod test {
         let _ = fs::remove_file(path);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn expect_summary_of_written_and_skipped_sessions() {
+        let written = vec![("work".to_string(), "/p/work.yml".into())];
+        let skipped = vec![("misc".to_string(), "/p/misc.yml".into())];
+
+        let failed = vec![("gone".to_string(), SnapshotError::ToPaneFailed)];
+
+        assert_eq!(
+            summary(&written, &skipped, &[]),
+            "We made a snapshot of 1 session(s)! \u{1F60A}\n    work -> /p/work.yml\n\
+             Skipped 1 session(s) with an existing project file, use -f to overwrite them:\n    misc -> /p/misc.yml\n"
+        );
+        assert_eq!(
+            summary(&written, &[], &failed),
+            "We made a snapshot of 1 session(s)! \u{1F60A}\n    work -> /p/work.yml\n\
+             Failed to snapshot 1 session(s):\n    gone: Failed to create pane from snapshot\n"
+        );
+        assert_eq!(summary(&[], &[], &[]), "");
+    }
 }
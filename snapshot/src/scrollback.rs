@@ -0,0 +1,117 @@
+//! Saving the output panes have printed, so a loaded snapshot can print it
+//! again. Each pane's history is written to its own file in a directory next
+//! to the project file, named after the project, like `my_project.scrollback/`.
+
+use crate::error::SnapshotError;
+use common::tmux::{Session, Target};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+type Result<T> = std::result::Result<T, SnapshotError>;
+
+/// The directory a project's scrollback files are saved to.
+pub fn directory(project_file: &Path) -> PathBuf {
+    project_file.with_extension("scrollback")
+}
+
+/// Point every pane of the session at the file its scrollback will be saved
+/// to, named by its window and pane index. Returns the panes to capture with
+/// their files.
+pub fn assign(session: &mut Session, directory: &Path) -> Vec<(Target, PathBuf)> {
+    let mut captures = vec![];
+
+    for window in &mut session.windows {
+        for pane in &mut window.panes {
+            let Some(target) = pane.target.as_ref() else {
+                continue;
+            };
+            let (Some(window), Some(index)) = (target.window, target.pane) else {
+                continue;
+            };
+
+            let file = directory.join(format!("{}.{}", window, index));
+            captures.push((target.clone(), file.clone()));
+            pane.scrollback = Some(file);
+        }
+    }
+
+    captures
+}
+
+/// Save the whole history of each pane to its file.
+pub fn save(captures: &[(Target, PathBuf)]) -> Result<()> {
+    for (target, file) in captures {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(file, capture(target)?)?;
+    }
+
+    Ok(())
+}
+
+/// The pane's history, from the start of its scrollback. The blank lines tmux
+/// pads the visible part of the pane with are left off.
+fn capture(target: &Target) -> Result<String> {
+    let output = Command::new("tmux")
+        .args(["capture-pane", "-p", "-S", "-", "-t", &target.combined])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(SnapshotError::CaptureFailed(target.combined.clone()));
+    }
+
+    let history = String::from_utf8_lossy(&output.stdout);
+    let history = history.trim_end();
+
+    Ok(match history.is_empty() {
+        true => String::new(),
+        false => format!("{}\n", history),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Pane, Window};
+
+    #[test]
+    fn names_files_after_window_and_pane() {
+        let target = Target::new("muxed", Some(1), None);
+        let mut session = Session {
+            windows: vec![Window {
+                panes: vec![
+                    Pane {
+                        target: Some(target.extend(0).unwrap()),
+                        ..Default::default()
+                    },
+                    Pane {
+                        target: Some(target.extend(1).unwrap()),
+                        ..Default::default()
+                    },
+                ],
+                target: Some(target),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let dir = directory(Path::new("/home/me/.muxed/muxed.yml"));
+        let captures = assign(&mut session, &dir);
+
+        let files: Vec<_> = captures.iter().map(|(_, file)| file.clone()).collect();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("/home/me/.muxed/muxed.scrollback/1.0"),
+                PathBuf::from("/home/me/.muxed/muxed.scrollback/1.1"),
+            ]
+        );
+        assert_eq!(
+            session.windows[0].panes[1].scrollback,
+            Some(files[1].clone())
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::entity::Entity;
 use crate::error::SnapshotError;
 use common::DEBUG;
-use common::tmux::{Pane, Session, Target, Window};
+use common::tmux::{Pane, Session, Target, Window, layout};
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::process::Output;
@@ -76,6 +76,127 @@ fn clear_redundant_paths(windows: &mut BTreeMap<usize, Window>, root: &Option<Pa
     }
 }
 
+/// Check that the layout tmux reports for a window describes the panes listed
+/// in it. A layout for a different number of panes is fitted to them, and one
+/// whose panes are sized differently is replaced by the nearest preset, so the
+/// snapshot always loads. A zoomed pane fills the window, so it's let through.
+fn check_layout(window_layout: String, sizes: &[(u32, u32)]) -> String {
+    let cell = match layout::Cell::parse(&window_layout) {
+        Some(cell) if !sizes.is_empty() && !layout::is_preset(&window_layout) => cell,
+        _ => return window_layout,
+    };
+
+    let panes = cell.panes();
+    if panes.len() != sizes.len() {
+        return layout::fit(&window_layout, sizes.len());
+    }
+
+    let whole = (cell.width, cell.height);
+    let described = panes
+        .iter()
+        .zip(sizes)
+        .all(|(pane, size)| (pane.width, pane.height) == *size || *size == whole);
+
+    match described {
+        true => window_layout,
+        false => cell.nearest_preset().to_string(),
+    }
+}
+
+/// Read a line tmux printed about a window or a pane. A pane's start command
+/// follows its JSON, after a space.
+fn parse_line(line: &str) -> Result<Option<Entity>> {
+    let mut stream = serde_json::Deserializer::from_str(line).into_iter::<Entity>();
+    let mut entity = match stream.next() {
+        Some(entity) => entity?,
+        None => return Ok(None),
+    };
+
+    let rest = &line[stream.byte_offset()..];
+    if let Entity::Pane { start_command, .. } = &mut entity {
+        *start_command = rest.strip_prefix(' ').and_then(unquote);
+    }
+
+    Ok(Some(entity))
+}
+
+/// Undo tmux's quoting of a start command. tmux quotes each of the command's
+/// args, so one arg, the command line a shell was started with, is unquoted.
+/// A command of several args was run without a shell, and as tmux prints it
+/// it can be typed in one, so it's left as it is.
+fn unquote(start: &str) -> Option<String> {
+    if start.is_empty() {
+        return None;
+    }
+
+    match words(start).as_deref() {
+        Some([word]) => Some(word.clone()),
+        _ => Some(start.to_string()),
+    }
+}
+
+/// Split a command tmux printed in to its args, or `None` when it isn't
+/// quoted the way tmux quotes. Double quoted and bare words escape with `\`,
+/// single quoted ones don't.
+fn words(start: &str) -> Option<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = start.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => word.push(unescape(&mut chars)?),
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_default().push(unescape(&mut chars)?),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+
+    Some(words)
+}
+
+/// The character a `\` escape stands for. tmux writes control characters C
+/// style, in octal when they have no name of their own.
+fn unescape(chars: &mut std::str::Chars) -> Option<char> {
+    let c = match chars.next()? {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'a' => '\x07',
+        'b' => '\x08',
+        'f' => '\x0c',
+        'v' => '\x0b',
+        digit @ '0'..='7' => {
+            let octal: String = std::iter::once(digit)
+                .chain(chars.by_ref().take(2))
+                .collect();
+            char::from(u8::from_str_radix(&octal, 8).ok()?)
+        }
+        c => c,
+    };
+
+    Some(c)
+}
+
 #[derive(Debug)]
 pub struct SessionOutput {
     pub output: Output,
@@ -87,13 +208,16 @@ impl TryFrom<SessionOutput> for Session {
 
     fn try_from(session: SessionOutput) -> Result<Self> {
         let mut windows: BTreeMap<usize, Window> = BTreeMap::new();
+        let mut sizes: BTreeMap<usize, Vec<(u32, u32)>> = BTreeMap::new();
 
         for line in String::from_utf8_lossy(&session.output.stdout).lines() {
             if DEBUG.load() {
                 println!("line {}", line);
             }
 
-            let entity: Entity = serde_json::from_str(line)?;
+            let Some(entity) = parse_line(line)? else {
+                continue;
+            };
 
             // Windows are always listed first. So we can assume all windows are present when we parse panes.
             match &entity {
@@ -104,12 +228,21 @@ impl TryFrom<SessionOutput> for Session {
                 p @ Entity::Pane { window_index, .. } => {
                     let pane = Pane::try_from(p)?;
                     if let Some(window) = windows.get_mut(window_index) {
-                        window.panes.push(pane)
+                        window.panes.push(pane);
+                        sizes.entry(*window_index).or_default().extend(p.size());
                     }
                 }
             }
         }
 
+        for (index, window) in windows.iter_mut() {
+            let sizes = sizes.get(index).map(Vec::as_slice).unwrap_or_default();
+            window.layout = window
+                .layout
+                .take()
+                .map(|layout| check_layout(layout, sizes));
+        }
+
         // Derive window paths from panes (most common path in each window)
         for window in windows.values_mut() {
             window.path = derive_window_path(&window.panes);
@@ -124,13 +257,18 @@ impl TryFrom<SessionOutput> for Session {
 
         Ok(Session {
             name: Some(session.target.combined.clone()),
+            extends: None,
             pre: None,
+            on_stop: None,
+            post: None,
             pre_window: None,
+            pre_pane: None,
             root,
             windows: windows.into_values().collect(),
             target: Some(session.target),
             daemonize: None,
             config: None,
+            vars: None,
         })
     }
 }
@@ -247,6 +385,57 @@ mod test {
         assert_eq!(window1.panes.len(), 1);
     }
 
+    #[test]
+    fn test_keeps_layouts_that_describe_the_panes() {
+        let target = Target::new("test-session", None, None);
+        let json_output = r#"{"type":"window","session":"test-session","index":0,"name":"main","active":1,"layout":"d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"}
+{"type":"window","session":"test-session","index":1,"name":"zoomed","active":0,"layout":"d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"}
+{"type":"pane","session":"test-session","window_index":0,"index":0,"active":1,"path":"/home/user","pid":12345,"width":79,"height":48}
+{"type":"pane","session":"test-session","window_index":0,"index":1,"active":0,"path":"/tmp","pid":12346,"width":79,"height":48}
+{"type":"pane","session":"test-session","window_index":1,"index":0,"active":1,"path":"/home/user","pid":12347,"width":159,"height":48}
+{"type":"pane","session":"test-session","window_index":1,"index":1,"active":0,"path":"/tmp","pid":12348,"width":79,"height":48}
+"#;
+
+        let session_output = SessionOutput {
+            output: create_mock_output(json_output, "", true),
+            target: target.clone(),
+        };
+
+        let session = Session::try_from(session_output).unwrap();
+        for window in &session.windows {
+            assert_eq!(
+                window.layout.as_deref(),
+                Some("d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}")
+            );
+        }
+    }
+
+    #[test]
+    fn test_replaces_layouts_that_dont_describe_the_panes() {
+        let target = Target::new("test-session", None, None);
+        let json_output = r#"{"type":"window","session":"test-session","index":0,"name":"resized","active":1,"layout":"d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"}
+{"type":"window","session":"test-session","index":1,"name":"fewer","active":0,"layout":"d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}"}
+{"type":"pane","session":"test-session","window_index":0,"index":0,"active":1,"path":"/home/user","pid":12345,"width":100,"height":48}
+{"type":"pane","session":"test-session","window_index":0,"index":1,"active":0,"path":"/tmp","pid":12346,"width":58,"height":48}
+{"type":"pane","session":"test-session","window_index":1,"index":0,"active":0,"path":"/tmp","pid":12347,"width":159,"height":48}
+"#;
+
+        let session_output = SessionOutput {
+            output: create_mock_output(json_output, "", true),
+            target: target.clone(),
+        };
+
+        let session = Session::try_from(session_output).unwrap();
+        assert_eq!(
+            session.windows[0].layout.as_deref(),
+            Some("even-horizontal")
+        );
+        assert_eq!(
+            session.windows[1].layout,
+            Some(layout::with_checksum("159x48,0,0,0"))
+        );
+    }
+
     #[test]
     fn test_session_from_pane_without_window() {
         let target = Target::new("test-session", None, None);
@@ -338,6 +527,7 @@ invalid json line
         assert_eq!(session.root, None);
         assert_eq!(session.daemonize, None);
         assert_eq!(session.config, None);
+        assert_eq!(session.vars, None);
     }
 
     #[test]
@@ -468,4 +658,54 @@ invalid json line
         let window1 = &session.windows[1];
         assert_eq!(window1.path, None);
     }
+
+    #[test]
+    fn test_pane_start_commands_as_tmux_prints_them() {
+        let target = Target::new("test-session", None, None);
+        let json_output = r#"{"type":"window","session":"test-session","index":0,"name":"main","active":1,"layout":"even-horizontal"}
+{"type":"pane","session":"test-session","window_index":0,"index":0,"active":1,"path":"/tmp","pid":12345} top
+{"type":"pane","session":"test-session","window_index":0,"index":1,"active":0,"path":"/tmp","pid":12346} "npm run dev -- --name \"web\""
+{"type":"pane","session":"test-session","window_index":0,"index":2,"active":0,"path":"/tmp","pid":12347} tail -f "/var/log/app log"
+"#;
+
+        let session_output = SessionOutput {
+            output: create_mock_output(json_output, "", true),
+            target,
+        };
+
+        let session = Session::try_from(session_output).unwrap();
+        let commands: Vec<Option<&str>> = session.windows[0]
+            .panes
+            .iter()
+            .map(|pane| pane.command.as_deref())
+            .collect();
+
+        assert_eq!(
+            commands,
+            vec![
+                Some("top"),
+                Some("npm run dev -- --name \"web\""),
+                Some("tail -f \"/var/log/app log\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_splits_tmux_quoted_words() {
+        assert_eq!(words("top"), Some(vec!["top".to_string()]));
+        assert_eq!(
+            words(r#""sleep 100 # \"a b\" it's \\x""#),
+            Some(vec![r#"sleep 100 # "a b" it's \x"#.to_string()])
+        );
+        assert_eq!(
+            words(r#"echo 'a "b"' \~ "1\t2""#),
+            Some(vec![
+                "echo".to_string(),
+                "a \"b\"".to_string(),
+                "~".to_string(),
+                "1\t2".to_string(),
+            ])
+        );
+        assert_eq!(words("\"unterminated"), None);
+    }
 }
@@ -1,12 +1,19 @@
 //! Muxed. A tmux project manager with no runtime dependencies.
 extern crate autocomplete;
 extern crate common;
+extern crate diff;
 extern crate docopt;
 extern crate edit;
+extern crate export;
 extern crate list;
 extern crate load;
 extern crate new;
+extern crate restart;
+extern crate schema;
 extern crate snapshot;
+extern crate stop;
+extern crate validate;
+extern crate workspace;
 
 use common::DEBUG;
 use common::args::Args;
@@ -27,43 +34,87 @@ macro_rules! try_or_err (
     })
 );
 
-static DISALLOWED_SHORTHAND_PROJECT_NAMES: [&str; 5] =
-    ["autocomplete", "new", "edit", "load", "snapshot"];
+static DISALLOWED_SHORTHAND_PROJECT_NAMES: [&str; 12] = [
+    "autocomplete",
+    "diff",
+    "new",
+    "edit",
+    "export",
+    "load",
+    "restart",
+    "schema",
+    "snapshot",
+    "stop",
+    "validate",
+    "workspace",
+];
 
 static USAGE: &str = "
 Usage:
     muxed autocomplete
     muxed (list | ls) [-1]
-    muxed [flags] [options] <project>
+    muxed diff [--json] [options] [<project>] [<vars>...]
     muxed edit [options] <project>
-    muxed load [flags] [options] <project>
+    muxed export [flags] [options] <project> [<vars>...]
+    muxed load [flags] [options] [<project>] [<vars>...]
     muxed new [flags] [options] <project>
-    muxed snapshot [flags] [options] <project>
+    muxed restart [flags] [options] [<project>] [<vars>...]
+    muxed schema
+    muxed snapshot [--with-scrollback] [flags] [options] <project>
+    muxed snapshot --all [--with-scrollback] [flags] [options]
+    muxed stop [--dry-run] [options] [<project>] [<vars>...]
+    muxed validate [--all] [options] [<project>]
+    muxed workspace (up | down) [-d] [--dry-run] [--keep-going] [options] <workspace>
+    muxed [flags] [options] [<project>] [<vars>...]
     muxed (-h | --help)
     muxed (-v | --version)
 
 Flags:
+    --all               Validate every project file, or snapshot every running session
     -d                  If you want to create a muxed session without connecting to it
     -f                  Overwrite existing file if one exists
+    --reconcile         Create the windows and panes missing from a running session before attaching
+    --into-current      Add the project's windows to the session muxed is run from
     --debug             Prints debug information while executing (project opening only)
+    --dry-run           Prints the tmux commands for a project without running them
     -h, --help          Prints help information
+    --json              Prints the diff as JSON
+    --keep-going        Builds the rest of a project when a tmux command fails instead of killing the session
+    --with-scrollback   Saves each pane's history with a snapshot and replays it on load
     -v, --version       Prints version information
 
 Options:
     -p <project_dir>              The directory your project config files live in. Defaults to ~/.muxed/
-    -t <session>                  The name of the running TMUX session to codify
+    -t <session>                  The name of the running TMUX session to codify or compare with
+    --into <session>              Add the project's windows to a running session instead of creating one
+    --format <format>             The format to export a project to. Supports: sh
+    --backend <backend>           How load sends commands to tmux: batch in to a few tmux calls, each in its own, or control over one control mode client. Defaults to batch
     --template <template_path>    The path to the template file to use for the new project
+    --allow <programs>            Only record pane commands run by these comma separated programs in a snapshot
+    --deny <programs>             Never record pane commands run by these comma separated programs in a snapshot
 
 Args:
-    <project>           The name of your project to open
+    <project>           The name of your project to open. Without one, the nearest .muxed.yml
+                        or muxed.yml in the current directory or its parents is opened
+    <vars>              Values for the project's template variables as key=value
+    <workspace>         The name of a workspace file in the workspaces directory of the project directory
 
 Subcommands:
     autocomplete                     Create autocompletions for bash, fish, or zsh
     list                             List the available project configs
+    diff <project>                   Show how a running session differs from its project file
     edit <project>                   Edit an existing project file
+    export --format sh <project>     Print a standalone shell script that builds the project
     load <project>                   Load the specified project, this is the default command
     new <project>                    To create a new project file
+    restart <project>                Stop a running project's session and load it again
+    schema                           Print the JSON Schema for project files
     snapshot -t <session> <project>  Capture a running session and create a config file for it
+    snapshot --all                   Capture every running session in to a config file named after it
+    stop <project>                   Run the project's on_stop hooks and kill its session
+    validate <project>               Check a project file for problems without loading it
+    workspace up <workspace>         Load every project in a workspace and attach to the first one
+    workspace down <workspace>       Stop every running project in a workspace
 ";
 
 /// The main execution method.
@@ -102,14 +153,28 @@ pub fn main() {
         exit(0);
     };
 
-    if args.cmd_edit {
+    if args.cmd_diff {
+        try_or_err!(diff::diff(args));
+    } else if args.cmd_edit {
         try_or_err!(edit::edit(args));
+    } else if args.cmd_export {
+        try_or_err!(export::export(args));
     } else if args.cmd_load {
         try_or_err!(load::load(args));
     } else if args.cmd_new {
         try_or_err!(new::new(args));
+    } else if args.cmd_restart {
+        try_or_err!(restart::restart(args));
+    } else if args.cmd_schema {
+        try_or_err!(schema::schema());
     } else if args.cmd_snapshot {
         try_or_err!(snapshot::snapshot(args));
+    } else if args.cmd_stop {
+        try_or_err!(stop::stop(args));
+    } else if args.cmd_validate {
+        try_or_err!(validate::validate(args));
+    } else if args.cmd_workspace {
+        try_or_err!(workspace::workspace(args));
     } else if args.cmd_autocomplete {
         try_or_err!(autocomplete::autocomplete(args))
     } else if args.cmd_list || args.cmd_ls {
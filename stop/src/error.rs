@@ -0,0 +1,36 @@
+use load::error::LoadError;
+use load::tmux::error::TmuxError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum StopError {
+    Load(LoadError),
+    NotRunning(String),
+    Tmux(TmuxError),
+}
+
+impl fmt::Display for StopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StopError::Load(e) => write!(f, "{}", e),
+            StopError::NotRunning(session) => {
+                write!(f, "There's no running tmux session named `{}`", session)
+            }
+            StopError::Tmux(e) => write!(f, "Couldn't stop the session: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StopError {}
+
+impl From<LoadError> for StopError {
+    fn from(err: LoadError) -> StopError {
+        StopError::Load(err)
+    }
+}
+
+impl From<TmuxError> for StopError {
+    fn from(err: TmuxError) -> StopError {
+        StopError::Tmux(err)
+    }
+}
@@ -0,0 +1,288 @@
+//! Muxedstop. Tears down a project's running tmux session.
+pub mod error;
+
+extern crate common;
+extern crate load;
+
+use crate::error::StopError;
+use common::args::Args;
+use common::tmux::{Session, Target, Window};
+use load::backend::{self, Backend, Policy};
+use load::command::{Commands, Keys, KillSession, RunShell};
+use load::shell;
+use load::tmux::{has_session, pane_idle};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// How long panes sent stop keys get to go back to their shell before the
+/// session is killed.
+static STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The main execution method.
+/// Reads the project the way `load` would and, when its session is running,
+/// sends each pane its `stop_keys`, waits for the programs they stop to exit,
+/// runs the `on_stop` commands inside the session and then kills it. With
+/// `--dry-run` the tmux commands are printed instead.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed stop my_project
+/// $ ./muxed stop --dry-run my_project
+/// ```
+pub fn stop(args: Args) -> Result<(), StopError> {
+    let (project, _) = load::plan(&args, true)?;
+    let name = project.name();
+
+    if !has_session(name) {
+        return Err(StopError::NotRunning(name.to_string()));
+    }
+
+    if args.flag_dry_run {
+        for command in &plan(project.session(), name) {
+            println!("{}", shell::render(command));
+        }
+        return Ok(());
+    }
+
+    run(project.session(), name)
+}
+
+/// Tear down the running session `name` of an enriched project. The stop keys
+/// are sent first and the panes given time to go back to their shell before
+/// the `on_stop` commands run and the session is killed. It stops at the first
+/// command tmux fails to run, or `on_stop` command that exits unsuccessfully.
+pub fn run(session: &Session, name: &str) -> Result<(), StopError> {
+    let (keys, rest): (Vec<Commands>, Vec<Commands>) = plan(session, name)
+        .into_iter()
+        .partition(|command| matches!(command, Commands::Keys(_)));
+
+    call(&keys, name)?;
+    wait_for_shells(&keys.iter().collect::<Vec<_>>());
+    call(&rest, name)
+}
+
+fn call(commands: &[Commands], name: &str) -> Result<(), StopError> {
+    backend::run(commands, Backend::Each, name, Policy::Abort)
+        .map_err(|mut failures| StopError::Tmux(failures.remove(0).error))
+}
+
+/// Wait for the panes that were sent stop keys to be back at their shell, for
+/// up to `STOP_TIMEOUT`. Whatever is still running after that is killed with
+/// the session.
+pub fn wait_for_shells(keys: &[&Commands]) {
+    let mut targets: Vec<&str> = keys
+        .iter()
+        .filter_map(|command| match command {
+            Commands::Keys(keys) => Some(keys.target.combined.as_str()),
+            _ => None,
+        })
+        .collect();
+    let start = Instant::now();
+
+    loop {
+        targets.retain(|target| !pane_idle(target));
+        if targets.is_empty() || start.elapsed() >= STOP_TIMEOUT {
+            return;
+        }
+        sleep(Duration::from_millis(100));
+    }
+}
+
+/// Plan the teardown of an enriched session named `name`. Panes are sent their stop keys
+/// first so servers shut down while the session is still intact, then the
+/// `on_stop` commands run in order in the session root, and the session is
+/// killed last. A window's
+/// `stop_keys` go to each of its panes that doesn't have its own.
+pub fn plan(session: &Session, name: &str) -> Vec<Commands> {
+    let target = Target::new(name, None, None);
+    let mut commands: Vec<Commands> = vec![];
+
+    for window in &session.windows {
+        for (target, keys) in stop_keys(window) {
+            if !keys.is_empty() {
+                commands.push(Keys::new(target.clone(), keys.to_vec()).into());
+            }
+        }
+    }
+
+    if let Some(on_stop) = session.on_stop.as_ref() {
+        for exec in on_stop.iter() {
+            let run = RunShell::new(target.clone(), session.root.clone(), exec);
+            commands.push(run.into());
+        }
+    }
+
+    commands.push(KillSession::new(name).into());
+    commands
+}
+
+/// The keys to send each pane of a window, or the window itself when it isn't
+/// split.
+fn stop_keys(window: &Window) -> Vec<(&Target, &[String])> {
+    let leaves = window.leaves();
+    if leaves.is_empty() {
+        return window
+            .target
+            .iter()
+            .map(|target| (target, window.stop_keys.as_slice()))
+            .collect();
+    }
+
+    leaves
+        .into_iter()
+        .filter_map(|pane| {
+            let keys = match pane.stop_keys.is_empty() {
+                true => &window.stop_keys,
+                false => &pane.stop_keys,
+            };
+            pane.target.as_ref().map(|target| (target, keys.as_slice()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::{Pane, Pre};
+    use std::path::PathBuf;
+
+    fn args(commands: &[Commands]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| command.as_trait().args().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn kills_a_session_without_hooks() {
+        let commands = plan(&Session::default(), "muxed");
+        assert_eq!(args(&commands), vec!["kill-session -t muxed"]);
+    }
+
+    #[test]
+    fn sends_stop_keys_then_runs_hooks_then_kills() {
+        let target = Target::new("muxed", Some(1), None);
+        let session = Session {
+            root: Some(PathBuf::from("/src/app")),
+            on_stop: Some(Pre(vec![
+                "docker compose down".into(),
+                "rm -f tmp/pids/server.pid".into(),
+            ])),
+            windows: vec![Window {
+                name: "server".into(),
+                panes: vec![
+                    Pane {
+                        stop_keys: vec!["C-c".into()],
+                        target: Some(target.extend(0).unwrap()),
+                        ..Default::default()
+                    },
+                    Pane {
+                        target: Some(target.extend(1).unwrap()),
+                        ..Default::default()
+                    },
+                ],
+                target: Some(target.clone()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args(&plan(&session, "muxed")),
+            vec![
+                "send-keys -t muxed:1.0 C-c",
+                "run-shell -t muxed -c /src/app docker compose down",
+                "run-shell -t muxed -c /src/app rm -f tmp/pids/server.pid",
+                "kill-session -t muxed",
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_stop_keys_to_nested_panes() {
+        let target = Target::new("muxed", Some(0), None);
+        let session = Session {
+            windows: vec![Window {
+                name: "server".into(),
+                panes: vec![
+                    Pane {
+                        target: Some(target.extend(0).unwrap()),
+                        ..Default::default()
+                    },
+                    Pane {
+                        panes: vec![
+                            Pane {
+                                stop_keys: vec!["C-c".into()],
+                                target: Some(target.extend(1).unwrap()),
+                                ..Default::default()
+                            },
+                            Pane {
+                                stop_keys: vec!["q".into()],
+                                target: Some(target.extend(2).unwrap()),
+                                ..Default::default()
+                            },
+                        ],
+                        ..Default::default()
+                    },
+                ],
+                target: Some(target.clone()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args(&plan(&session, "muxed")),
+            vec![
+                "send-keys -t muxed:0.1 C-c",
+                "send-keys -t muxed:0.2 q",
+                "kill-session -t muxed",
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_window_stop_keys_to_panes_without_their_own() {
+        let session = Session {
+            windows: vec![
+                Window {
+                    name: "server".into(),
+                    stop_keys: vec!["C-c".into()],
+                    target: Some(Target::new("muxed", Some(0), None)),
+                    ..Default::default()
+                },
+                Window {
+                    name: "workers".into(),
+                    stop_keys: vec!["C-c".into()],
+                    panes: vec![
+                        Pane {
+                            target: Some(Target::new("muxed", Some(1), Some(0))),
+                            ..Default::default()
+                        },
+                        Pane {
+                            stop_keys: vec!["q".into()],
+                            target: Some(Target::new("muxed", Some(1), Some(1))),
+                            ..Default::default()
+                        },
+                    ],
+                    target: Some(Target::new("muxed", Some(1), None)),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args(&plan(&session, "muxed")),
+            vec![
+                "send-keys -t muxed:0 C-c",
+                "send-keys -t muxed:1.0 C-c",
+                "send-keys -t muxed:1.1 q",
+                "kill-session -t muxed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Checks for projects that parse but wouldn't load the way they read.
+
+use common::project_paths::expand_path;
+use common::tmux::{Pane, Session, layout};
+use std::path::Path;
+
+/// Look over a parsed session for problems tmux or muxed would trip on. Paths
+/// are only checked when `check_paths` is set, since they can't be trusted
+/// while template variables are left unresolved.
+pub fn check(session: &Session, check_paths: bool) -> Vec<String> {
+    let mut problems = vec![];
+
+    if session.windows.is_empty() {
+        problems.push("The project doesn't define any windows".to_string());
+    }
+
+    let mut seen: Vec<&str> = vec![];
+    let mut duplicates: Vec<&str> = vec![];
+    for window in &session.windows {
+        if seen.contains(&window.name()) && !duplicates.contains(&window.name()) {
+            duplicates.push(window.name());
+        }
+        seen.push(window.name());
+    }
+    for name in duplicates {
+        problems.push(format!("The window name `{}` is used more than once", name));
+    }
+
+    let active: Vec<&str> = session
+        .windows
+        .iter()
+        .filter(|window| window.active)
+        .map(|window| window.name())
+        .collect();
+    if active.len() > 1 {
+        problems.push(format!(
+            "Only one window can be active, but these are: `{}`",
+            active.join("`, `")
+        ));
+    }
+
+    for window in &session.windows {
+        if window.leaves().iter().filter(|pane| pane.active).count() > 1 {
+            problems.push(format!(
+                "The window `{}` has more than one active pane",
+                window.name()
+            ));
+        }
+
+        if has_split_commands(&window.panes) {
+            problems.push(format!(
+                "The window `{}` has a pane with both a `command` and `panes`, its command won't run",
+                window.name()
+            ));
+        }
+
+        let custom = window.layout.as_ref().filter(|l| !layout::is_preset(l));
+        match custom.map(|l| (l, layout::Cell::parse(l))) {
+            Some((layout, None)) => problems.push(format!(
+                "The window `{}` has the layout `{}`, which is neither one of `{}` nor a custom layout",
+                window.name(),
+                layout,
+                layout::PRESETS.join("`, `")
+            )),
+            Some((_, Some(cell))) if cell.panes().len() < window.leaves().len() => {
+                problems.push(format!(
+                    "The window `{}` has {} panes but its layout only has room for {}, `{}` will be used instead",
+                    window.name(),
+                    window.leaves().len(),
+                    cell.panes().len(),
+                    cell.nearest_preset()
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    if check_paths {
+        problems.extend(check_paths_exist(session));
+    }
+
+    problems
+}
+
+fn check_paths_exist(session: &Session) -> Vec<String> {
+    let mut problems = vec![];
+
+    if let Some(root) = session.root.as_ref().filter(|root| is_missing(root)) {
+        problems.push(format!("The root `{}` doesn't exist", root.display()));
+    }
+
+    for window in &session.windows {
+        if let Some(path) = window.path.as_ref().filter(|path| is_missing(path)) {
+            problems.push(format!(
+                "The path `{}` for the window `{}` doesn't exist",
+                path.display(),
+                window.name()
+            ));
+        }
+
+        for (index, pane) in window.leaves().into_iter().enumerate() {
+            if let Some(path) = pane.path.as_ref().filter(|path| is_missing(path)) {
+                problems.push(format!(
+                    "The path `{}` for pane {} of the window `{}` doesn't exist",
+                    path.display(),
+                    index,
+                    window.name()
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// A pane split in to panes is made up of them, so a command of its own has
+/// nowhere to run.
+fn has_split_commands(panes: &[Pane]) -> bool {
+    panes.iter().any(|pane| {
+        (!pane.panes.is_empty() && pane.command.is_some()) || has_split_commands(&pane.panes)
+    })
+}
+
+/// Relative paths depend on where muxed is run from, so only absolute paths,
+/// after expanding `~`, are checked.
+fn is_missing(path: &Path) -> bool {
+    match expand_path(path) {
+        Some(path) => path.is_absolute() && !path.is_dir(),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::Window;
+    use std::env::temp_dir;
+    use std::path::PathBuf;
+
+    fn window(name: &str) -> Window {
+        Window {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_a_plain_session() {
+        let session = Session {
+            root: Some(temp_dir()),
+            windows: vec![window("editor"), window("git")],
+            ..Default::default()
+        };
+        assert!(check(&session, true).is_empty());
+    }
+
+    #[test]
+    fn reports_missing_windows() {
+        let problems = check(&Session::default(), true);
+        assert_eq!(problems, vec!["The project doesn't define any windows"]);
+    }
+
+    #[test]
+    fn reports_duplicate_window_names_once() {
+        let session = Session {
+            windows: vec![window("editor"), window("editor"), window("editor")],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, false),
+            vec!["The window name `editor` is used more than once"]
+        );
+    }
+
+    #[test]
+    fn reports_more_than_one_active_window() {
+        let session = Session {
+            windows: vec![
+                Window {
+                    active: true,
+                    ..window("editor")
+                },
+                Window {
+                    active: true,
+                    ..window("git")
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, false),
+            vec!["Only one window can be active, but these are: `editor`, `git`"]
+        );
+    }
+
+    #[test]
+    fn reports_more_than_one_active_pane() {
+        let active = Pane {
+            active: true,
+            ..Default::default()
+        };
+        let session = Session {
+            windows: vec![Window {
+                panes: vec![active.clone(), active],
+                ..window("editor")
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, false),
+            vec!["The window `editor` has more than one active pane"]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_layouts() {
+        let session = Session {
+            windows: vec![
+                Window {
+                    layout: Some("main-vertical".into()),
+                    ..window("editor")
+                },
+                Window {
+                    layout: Some("main_vertical".into()),
+                    ..window("git")
+                },
+            ],
+            ..Default::default()
+        };
+        let problems = check(&session, false);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("The window `git` has the layout `main_vertical`"));
+    }
+
+    #[test]
+    fn reports_custom_layouts_too_small_for_their_panes() {
+        let session = Session {
+            windows: vec![
+                Window {
+                    // A stale checksum is re-derived when the project loads.
+                    layout: Some("0000,159x48,0,0{79x48,0,0,0,79x48,80,0,1}".into()),
+                    panes: vec![Pane::default(), Pane::default()],
+                    ..window("editor")
+                },
+                Window {
+                    layout: Some("d463,159x48,0,0{79x48,0,0,0,79x48,80,0,1}".into()),
+                    panes: vec![Pane::default(), Pane::default(), Pane::default()],
+                    ..window("git")
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, false),
+            vec![
+                "The window `git` has 3 panes but its layout only has room for 2, `even-horizontal` will be used instead"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_absolute_paths() {
+        let missing = PathBuf::from("/muxed/does/not/exist");
+        let session = Session {
+            root: Some(missing.clone()),
+            windows: vec![Window {
+                path: Some(PathBuf::from("relative/is/skipped")),
+                panes: vec![Pane {
+                    path: Some(missing),
+                    ..Default::default()
+                }],
+                ..window("editor")
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, true),
+            vec![
+                "The root `/muxed/does/not/exist` doesn't exist",
+                "The path `/muxed/does/not/exist` for pane 0 of the window `editor` doesn't exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_commands_on_panes_split_in_to_panes() {
+        let session = Session {
+            windows: vec![Window {
+                panes: vec![
+                    Pane::default(),
+                    Pane {
+                        command: Some("htop".into()),
+                        panes: vec![Pane::default(), Pane::default()],
+                        ..Default::default()
+                    },
+                ],
+                ..window("editor")
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&session, false),
+            vec![
+                "The window `editor` has a pane with both a `command` and `panes`, its command won't run"
+            ]
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use common::error::CommonError;
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum ValidateError {
+    Common(CommonError),
+    Io(io::Error),
+    Invalid(usize),
+}
+
+impl fmt::Display for ValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidateError::Common(e) => write!(f, "{}", e),
+            ValidateError::Io(e) => write!(f, "IO error: {}", e),
+            ValidateError::Invalid(1) => write!(f, "1 problem was found"),
+            ValidateError::Invalid(count) => write!(f, "{} problems were found", count),
+        }
+    }
+}
+
+impl std::error::Error for ValidateError {}
+
+impl From<CommonError> for ValidateError {
+    fn from(err: CommonError) -> ValidateError {
+        ValidateError::Common(err)
+    }
+}
+
+impl From<io::Error> for ValidateError {
+    fn from(err: io::Error) -> ValidateError {
+        ValidateError::Io(err)
+    }
+}
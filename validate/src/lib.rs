@@ -0,0 +1,221 @@
+//! Muxedvalidate. Checks project files without loading them.
+mod check;
+mod error;
+
+extern crate common;
+extern crate load;
+
+use crate::error::ValidateError;
+use common::args::Args;
+use common::project_paths::{CONFIG_EXTENSION, ProjectPaths};
+use load::error::LoadError;
+use load::project::parse;
+use load::vars::{Vars, interpolate};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A problem found in a project file. Parse errors know where in the file they
+/// happened, problems with the project's contents don't.
+#[derive(Debug, PartialEq)]
+struct Diagnostic {
+    file: PathBuf,
+    location: Option<(u64, u64)>,
+    message: String,
+}
+
+impl Diagnostic {
+    fn new(file: &Path, message: String) -> Diagnostic {
+        Diagnostic {
+            file: file.to_path_buf(),
+            location: None,
+            message,
+        }
+    }
+
+    /// serde_saphyr appends the location to its messages. Split it back out so
+    /// it can lead the diagnostic the way compilers print them.
+    fn parse(file: &Path, error: &serde_saphyr::Error) -> Diagnostic {
+        let message = error.to_string();
+
+        match error.location() {
+            Some(location) => {
+                let suffix = format!(" at line {}, column {}", location.line(), location.column());
+                Diagnostic {
+                    file: file.to_path_buf(),
+                    location: Some((location.line(), location.column())),
+                    message: message
+                        .strip_suffix(&suffix)
+                        .unwrap_or(&message)
+                        .to_string(),
+                }
+            }
+            None => Diagnostic::new(file, message),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{}:{}:{}: {}",
+                self.file.display(),
+                line,
+                column,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// The main execution method.
+/// Parses the project, or with `--all` every project in the project directory,
+/// and prints each problem found with the file it's in. Errors when any
+/// problem was found so it can gate a CI run.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed validate my_project
+/// $ ./muxed validate --all
+/// ```
+pub fn validate(args: Args) -> Result<(), ValidateError> {
+    let files = if args.flag_all {
+        project_files(&ProjectPaths::try_from(&args)?)?
+    } else {
+        vec![ProjectPaths::resolve(&args)?.project_file]
+    };
+
+    let mut problems = 0;
+    for file in &files {
+        let diagnostics = validate_file(file);
+
+        if diagnostics.is_empty() {
+            println!("{}: ok", file.display());
+        }
+
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+
+        problems += diagnostics.len();
+    }
+
+    match problems {
+        0 => Ok(()),
+        count => Err(ValidateError::Invalid(count)),
+    }
+}
+
+/// Every project file in the project directory, leaving out the template.
+fn project_files(project_paths: &ProjectPaths) -> Result<Vec<PathBuf>, ValidateError> {
+    let mut files: Vec<PathBuf> = project_paths
+        .project_directory
+        .read_dir()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path != &project_paths.template_file)
+        .filter(|path| path.extension().and_then(|x| x.to_str()) == Some(CONFIG_EXTENSION))
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+fn validate_file(file: &Path) -> Vec<Diagnostic> {
+    let mut session = match parse(file) {
+        Ok(session) => session,
+        Err(LoadError::Parse(file, e)) => return vec![Diagnostic::parse(&file, &e)],
+        Err(e) => return vec![Diagnostic::new(file, e.to_string())],
+    };
+
+    let mut diagnostics = vec![];
+
+    // Without values from the command line only the `vars:` map and the
+    // environment can fill the placeholders in.
+    let resolved = match Vars::new(&[], session.vars.clone()) {
+        Ok(vars) => match interpolate(&mut session, &vars) {
+            Ok(()) => true,
+            Err(name) => {
+                let message = format!(
+                    "The variable `{}` has no value in `vars:` or the environment, so paths weren't checked",
+                    name
+                );
+                diagnostics.push(Diagnostic::new(file, message));
+                false
+            }
+        },
+        Err(_) => false,
+    };
+
+    diagnostics.extend(
+        check::check(&session, resolved)
+            .into_iter()
+            .map(|message| Diagnostic::new(file, message)),
+    );
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+    use std::env::temp_dir;
+    use std::fs;
+
+    fn validate_contents(contents: &str) -> Vec<Diagnostic> {
+        let file = temp_dir().join(format!("{}.yml", rand_names::project_file_name()));
+        fs::write(&file, contents).unwrap();
+        let diagnostics = validate_file(&file);
+        let _ = fs::remove_file(&file);
+        diagnostics
+    }
+
+    #[test]
+    fn reports_parse_errors_with_line_and_column() {
+        let diagnostics = validate_contents("windows:\n  - editor\n  - name: [1, 2\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].location.is_some());
+        assert!(!diagnostics[0].message.contains(" at line "));
+    }
+
+    #[test]
+    fn formats_located_diagnostics_like_a_compiler() {
+        let diagnostic = Diagnostic {
+            file: PathBuf::from("/tmp/project.yml"),
+            location: Some((3, 9)),
+            message: "unexpected event".to_string(),
+        };
+        assert_eq!(
+            diagnostic.to_string(),
+            "/tmp/project.yml:3:9: unexpected event"
+        );
+    }
+
+    #[test]
+    fn reports_semantic_problems() {
+        let diagnostics = validate_contents("windows: ['editor', 'editor']");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "The window name `editor` is used more than once"
+        );
+    }
+
+    #[test]
+    fn skips_path_checks_when_a_variable_is_unresolved() {
+        let diagnostics = validate_contents("root: /muxed/${muxed_not_a_var}\nwindows: ['editor']");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("`muxed_not_a_var`"));
+    }
+
+    #[test]
+    fn accepts_a_valid_project() {
+        assert!(validate_contents("windows: ['editor', 'git']").is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+use common::error::CommonError;
+use load::error::LoadError;
+use std::path::PathBuf;
+use std::{fmt, io};
+use stop::error::StopError;
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Common(CommonError),
+    Empty(PathBuf),
+    Load(LoadError),
+    Parse(PathBuf, serde_saphyr::Error),
+    Read(String, PathBuf, io::Error),
+    Stop(StopError),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkspaceError::Common(e) => write!(f, "{}", e),
+            WorkspaceError::Empty(file) => write!(
+                f,
+                "The workspace `{}` doesn't list any projects",
+                file.display()
+            ),
+            WorkspaceError::Load(e) => write!(f, "{}", e),
+            WorkspaceError::Parse(file, e) => {
+                write!(
+                    f,
+                    "Couldn't parse the workspace `{}`: {}",
+                    file.display(),
+                    e
+                )
+            }
+            WorkspaceError::Read(name, directory, e) => write!(
+                f,
+                "No workspace file was found with the name `{}` in the directory `{}`. Received error: {}",
+                name,
+                directory.display(),
+                e
+            ),
+            WorkspaceError::Stop(e) => write!(f, "Couldn't stop the workspace: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {}
+
+impl From<CommonError> for WorkspaceError {
+    fn from(err: CommonError) -> WorkspaceError {
+        WorkspaceError::Common(err)
+    }
+}
+
+impl From<LoadError> for WorkspaceError {
+    fn from(err: LoadError) -> WorkspaceError {
+        WorkspaceError::Load(err)
+    }
+}
+
+impl From<StopError> for WorkspaceError {
+    fn from(err: StopError) -> WorkspaceError {
+        WorkspaceError::Stop(err)
+    }
+}
@@ -0,0 +1,215 @@
+//! Workspace files. A workspace lists projects that are loaded and stopped
+//! together, each by name or with the vars to load it with:
+//!
+//! ```yaml
+//! projects:
+//!   - api
+//!   - project: worker
+//!     vars:
+//!       queue: critical
+//!     daemonize: true
+//! ```
+
+use crate::error::WorkspaceError;
+use common::args::Args;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory workspace files live in, inside the project directory.
+static WORKSPACE_FOLDER: &str = "workspaces";
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Workspace {
+    pub projects: Vec<Member>,
+}
+
+/// A project in a workspace.
+#[derive(Debug, Default, PartialEq)]
+pub struct Member {
+    /// The name of the project file, as it's given to `muxed load`.
+    pub project: String,
+    /// Values for the project's template variables.
+    pub vars: BTreeMap<String, String>,
+    /// Load the project without ever attaching to it.
+    pub daemonize: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MemberRepr {
+    Str(String),
+    Map {
+        project: String,
+        #[serde(default)]
+        vars: BTreeMap<String, String>,
+        #[serde(default)]
+        daemonize: bool,
+    },
+}
+
+impl<'de> Deserialize<'de> for Member {
+    /// A member can be written as just the project's name, or as a map when it
+    /// needs vars or to be daemonized.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match MemberRepr::deserialize(deserializer)? {
+            MemberRepr::Str(project) => Member {
+                project,
+                ..Default::default()
+            },
+            MemberRepr::Map {
+                project,
+                vars,
+                daemonize,
+            } => Member {
+                project,
+                vars,
+                daemonize,
+            },
+        })
+    }
+}
+
+impl Workspace {
+    /// The member attached to once the workspace is up: the first one that
+    /// isn't daemonized.
+    pub fn attach_to(&self) -> Option<usize> {
+        self.projects.iter().position(|member| !member.daemonize)
+    }
+}
+
+impl Member {
+    /// The args `load` is called with for the member. The project directory,
+    /// `--dry-run`, `--backend` and `--keep-going` are passed on from the
+    /// workspace's own args.
+    pub fn args(&self, workspace: &Args, daemonize: bool) -> Args {
+        Args {
+            arg_project: self.project.clone(),
+            arg_vars: self
+                .vars
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect(),
+            flag_d: daemonize,
+            flag_backend: workspace.flag_backend.clone(),
+            flag_dry_run: workspace.flag_dry_run,
+            flag_keep_going: workspace.flag_keep_going,
+            flag_p: workspace.flag_p.clone(),
+            cmd_load: true,
+            cmd_new: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// The path of the workspace file named `name`.
+pub fn path(project_directory: &Path, name: &str) -> PathBuf {
+    project_directory
+        .join(WORKSPACE_FOLDER)
+        .join(name)
+        .with_extension(common::project_paths::CONFIG_EXTENSION)
+}
+
+/// Read and parse the workspace file named `name`.
+pub fn read(project_directory: &Path, name: &str) -> Result<Workspace, WorkspaceError> {
+    let file = path(project_directory, name);
+
+    let contents = fs::read_to_string(&file).map_err(|e| {
+        WorkspaceError::Read(
+            name.to_string(),
+            project_directory.join(WORKSPACE_FOLDER),
+            e,
+        )
+    })?;
+    let workspace: Workspace =
+        serde_saphyr::from_str(&contents).map_err(|e| WorkspaceError::Parse(file.clone(), e))?;
+
+    match workspace.projects.is_empty() {
+        true => Err(WorkspaceError::Empty(file)),
+        false => Ok(workspace),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_maps() {
+        let workspace: Workspace = serde_saphyr::from_str(
+            "
+projects:
+  - api
+  - project: worker
+    vars:
+      queue: critical
+    daemonize: true
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            workspace.projects,
+            vec![
+                Member {
+                    project: "api".into(),
+                    ..Default::default()
+                },
+                Member {
+                    project: "worker".into(),
+                    vars: BTreeMap::from([("queue".into(), "critical".into())]),
+                    daemonize: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn attaches_to_the_first_member_not_daemonized() {
+        let member = |daemonize| Member {
+            daemonize,
+            ..Default::default()
+        };
+
+        let workspace = Workspace {
+            projects: vec![member(true), member(false), member(false)],
+        };
+        assert_eq!(workspace.attach_to(), Some(1));
+
+        let workspace = Workspace {
+            projects: vec![member(true)],
+        };
+        assert_eq!(workspace.attach_to(), None);
+    }
+
+    #[test]
+    fn passes_vars_to_load() {
+        let member = Member {
+            project: "worker".into(),
+            vars: BTreeMap::from([("queue".into(), "critical".into())]),
+            daemonize: false,
+        };
+        let args = member.args(&Args::default(), true);
+
+        assert_eq!(args.arg_project, "worker");
+        assert_eq!(args.arg_vars, vec!["queue=critical"]);
+        assert!(args.flag_d);
+    }
+
+    #[test]
+    fn passes_the_backend_and_policy_to_load() {
+        let workspace = Args {
+            flag_backend: Some("control".into()),
+            flag_keep_going: true,
+            ..Default::default()
+        };
+        let args = Member::default().args(&workspace, true);
+
+        assert_eq!(args.flag_backend.as_deref(), Some("control"));
+        assert!(args.flag_keep_going);
+    }
+}
@@ -0,0 +1,98 @@
+//! Muxedworkspace. Loads and stops a group of projects together.
+mod error;
+mod file;
+
+extern crate common;
+extern crate load;
+extern crate serde;
+extern crate stop;
+
+use crate::error::WorkspaceError;
+use crate::file::Workspace;
+use common::args::Args;
+use common::project_paths::ProjectPaths;
+use load::shell;
+use load::tmux;
+
+/// The main execution method.
+/// Reads the workspace file from `workspaces/` in the project directory. `up`
+/// loads each of its projects the way `load` would, daemonized, and attaches
+/// to the first one not marked `daemonize` last. Projects already running are
+/// left alone. `down` stops each running project the way `stop` would. With
+/// `--dry-run` the commands are printed instead.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```console
+/// $ ./muxed workspace up backend
+/// $ ./muxed workspace down backend
+/// ```
+pub fn workspace(args: Args) -> Result<(), WorkspaceError> {
+    let project_paths = ProjectPaths::try_from(&args)?;
+    let workspace = file::read(&project_paths.project_directory, &args.arg_workspace)?;
+
+    match args.cmd_up {
+        true => up(&args, &workspace),
+        false => down(&args, &workspace),
+    }
+}
+
+fn up(args: &Args, workspace: &Workspace) -> Result<(), WorkspaceError> {
+    let attach = workspace.attach_to().filter(|_| !args.flag_d);
+
+    for (index, member) in workspace.projects.iter().enumerate() {
+        if Some(index) == attach {
+            continue;
+        }
+
+        let member_args = member.args(args, true);
+        if !args.flag_dry_run && running(&member_args)? {
+            continue;
+        }
+
+        load::load(member_args)?;
+    }
+
+    // Attaching can replace or block muxed, so it's done last.
+    if let Some(member) = attach.map(|index| &workspace.projects[index]) {
+        load::load(member.args(args, false))?;
+    }
+
+    Ok(())
+}
+
+fn down(args: &Args, workspace: &Workspace) -> Result<(), WorkspaceError> {
+    let mut projects = vec![];
+    for member in &workspace.projects {
+        let (project, _) = load::plan(&member.args(args, true), true)?;
+        if tmux::has_session(project.name()) {
+            projects.push(project);
+        }
+    }
+
+    // Stopping the session muxed is run from takes muxed down with it, so it
+    // goes last.
+    let current = tmux::current_session();
+    projects.sort_by_key(|project| current.as_deref() == Some(project.name()));
+
+    for project in &projects {
+        if !args.flag_dry_run {
+            stop::run(project.session(), project.name())?;
+            continue;
+        }
+
+        for command in stop::plan(project.session(), project.name()) {
+            println!("{}", shell::render(&command));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the member's session is already running.
+fn running(member_args: &Args) -> Result<bool, WorkspaceError> {
+    let (project, _) = load::plan(member_args, true)?;
+    Ok(tmux::has_session(project.name()))
+}